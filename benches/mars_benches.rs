@@ -5,7 +5,7 @@ use test::Bencher;
 use test::black_box;
 
 extern crate libcw;
-use libcw::redcode::Instruction;
+use libcw::redcode::{Instruction, Warrior};
 use libcw::redcode::types::*;
 use libcw::simulation::{
     Mars,
@@ -25,7 +25,7 @@ fn mars_build_time(bench: &mut Bencher)
 #[bench]
 fn mars_build_and_load_time(bench: &mut Bencher)
 {
-    let prog = black_box(vec![
+    let prog = black_box(Warrior::from(vec![
         Instruction::new(
             OpCode::Mov,
             Modifier::I,
@@ -34,7 +34,7 @@ fn mars_build_and_load_time(bench: &mut Bencher)
             0,
             AddressingMode::Direct
         )
-    ]);
+    ]));
 
     let load = vec![(0, None, &prog)];
 
@@ -48,7 +48,7 @@ fn mars_build_and_load_time(bench: &mut Bencher)
 #[bench]
 fn mars_imp_sim_time(bench: &mut Bencher)
 {
-    let prog = black_box(vec![
+    let prog = black_box(Warrior::from(vec![
         Instruction::new(
             OpCode::Mov,
             Modifier::I,
@@ -57,7 +57,7 @@ fn mars_imp_sim_time(bench: &mut Bencher)
             0,
             AddressingMode::Direct
         )
-    ]);
+    ]));
 
     let load = black_box(vec![(0, None, &prog)]);
 
@@ -79,7 +79,7 @@ fn mars_imp_sim_time(bench: &mut Bencher)
 #[bench]
 fn mars_dwarf_sim_time(bench: &mut Bencher)
 {
-    let dwarf = vec![
+    let dwarf = Warrior::from(vec![
         Instruction::new(
             OpCode::Add,
             Modifier::AB,
@@ -112,7 +112,7 @@ fn mars_dwarf_sim_time(bench: &mut Bencher)
             0,
             AddressingMode::Direct
             ),
-    ]; 
+    ]); 
 
     let load = black_box(vec![(0, None, &dwarf)]);
 
@@ -134,7 +134,7 @@ fn mars_dwarf_sim_time(bench: &mut Bencher)
 #[bench]
 fn mars_imp_vs_dwarf_sim_time(bench: &mut Bencher)
 {
-    let imp = black_box(vec![
+    let imp = black_box(Warrior::from(vec![
         Instruction::new(
             OpCode::Mov,
             Modifier::I,
@@ -143,9 +143,9 @@ fn mars_imp_vs_dwarf_sim_time(bench: &mut Bencher)
             0,
             AddressingMode::Direct
         )
-    ]);
+    ]));
 
-    let dwarf = vec![
+    let dwarf = Warrior::from(vec![
         Instruction::new(
             OpCode::Add,
             Modifier::AB,
@@ -178,7 +178,7 @@ fn mars_imp_vs_dwarf_sim_time(bench: &mut Bencher)
             0,
             AddressingMode::Direct
             ),
-    ]; 
+    ]); 
 
     let load = black_box(vec![(2000, None, &dwarf), (4000, None, &imp)]);
 
@@ -6,7 +6,7 @@ use std::fmt;
 
 extern crate libcw;
 use libcw::redcode::types::*;
-use libcw::redcode::Instruction;
+use libcw::redcode::{Instruction, Warrior};
 use libcw::redcode::traits;
 use libcw::simulation::{MarsBuilder, Mars};
 
@@ -32,13 +32,13 @@ fn display_mars_state<T>(mars: &Mars<T>, margin: usize)
         pc - margin
     };
 
-    let iter = mars.memory().iter()
-        .enumerate()
+    let iter = (0..size)
         .cycle()
         .skip(min)
         .take(margin*2 + 1);
 
-    for (addr, ins) in iter {
+    for addr in iter {
+        let ins = mars.display_instruction(addr as Address);
         if addr == pc {
             println!(">{}< {}", addr, ins);
         } else {
@@ -49,7 +49,7 @@ fn display_mars_state<T>(mars: &Mars<T>, margin: usize)
 
 fn main()
 {
-    let dwarf = vec![
+    let dwarf = Warrior::from(vec![
         Instruction::new(
             OpCode::Add,
             Modifier::AB,
@@ -83,10 +83,11 @@ fn main()
             AddressingMode::Direct
             ),
 
-    ]; 
+    ]); 
 
     // create mars
     let mut mars = MarsBuilder::new()
+        .signed_display(true)
         .build_and_load(vec![(4000, None, &dwarf)])
         .unwrap();
 
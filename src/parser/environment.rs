@@ -0,0 +1,300 @@
+//! Settings of the `Mars` a program is assembled for
+
+use redcode::Standard;
+use redcode::types::{OpCode, Modifier, AddressingMode};
+use redcode::defaults::*;
+
+/// Number of rounds fought by default
+const DEFAULT_ROUNDS: usize = 1;
+
+/// Settings of the `Mars` a program is assembled for, exposed to the program
+/// as the predefined constants `CORESIZE`, `MAXPROCESSES`, ...
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Environment
+{
+    /// Size of core's memory buffer
+    core_size:     usize,
+
+    /// Size of each warrior's p-space
+    pspace_size:   usize,
+
+    /// Maximum number of cycles before game is considered a draw
+    max_cycles:    usize,
+
+    /// Maximum number of processes that can be in the process queue
+    max_processes: usize,
+
+    /// Maximum number of instructions a warrior can be comprised of
+    max_length:    usize,
+
+    /// Minimum distance between two warriors
+    min_distance:  usize,
+
+    /// Distance from the program counter instructions can read at, the
+    /// whole core if `None`
+    read_limit:    Option<usize>,
+
+    /// Distance from the program counter instructions can write at, the
+    /// whole core if `None`
+    write_limit:   Option<usize>,
+
+    /// Mars Version multiplied by 100
+    version:       usize,
+
+    /// Number of warriors in each round
+    warriors:      usize,
+
+    /// Number of rounds in the match
+    rounds:        usize,
+
+    /// Standard deciding the modifier of instructions written without one
+    standard:      Standard,
+}
+
+impl Environment
+{
+    /// Create an `Environment` with the same defaults as `MarsBuilder`
+    pub fn new() -> Self
+    {
+        Environment {
+            core_size:     DEFAULT_SIZE,
+            pspace_size:   DEFAULT_PSPACE_SIZE,
+            max_cycles:    DEFAULT_MAX_CYCLES,
+            max_processes: DEFAULT_MAX_PROCESSES,
+            max_length:    DEFAULT_MAX_LENGTH,
+            min_distance:  DEFAULT_MIN_DISTANCE,
+            read_limit:    None,
+            write_limit:   None,
+            version:       DEFAULT_VERSION,
+            warriors:      DEFAULT_WARRIORS,
+            rounds:        DEFAULT_ROUNDS,
+            standard:      Standard::default(),
+        }
+    }
+
+    /// Size of memory
+    ///
+    /// # Arguments
+    /// * `size`: size of memory
+    ///
+    /// # Return
+    /// `Self`
+    pub fn core_size(&mut self, size: usize) -> &mut Self
+    {
+        self.core_size = size;
+        self
+    }
+
+    /// Size of each warrior's P-space
+    ///
+    /// # Arguments
+    /// * `size`: size of p-space
+    ///
+    /// # Return
+    /// `Self`
+    pub fn pspace_size(&mut self, size: usize) -> &mut Self
+    {
+        self.pspace_size = size;
+        self
+    }
+
+    /// Maximum number of cycles that can elapse before a tie is declared
+    ///
+    /// # Arguments
+    /// * `n`: number of cycles
+    ///
+    /// # Return
+    /// `Self`
+    pub fn max_cycles(&mut self, n: usize) -> &mut Self
+    {
+        self.max_cycles = n;
+        self
+    }
+
+    /// Maximum number of processes a core can have in it's process queue
+    ///
+    /// # Arguments
+    /// * `n`: number of processes
+    ///
+    /// # Return
+    /// `Self`
+    pub fn max_processes(&mut self, n: usize) -> &mut Self
+    {
+        self.max_processes = n;
+        self
+    }
+
+    /// Maximum number of instructions allowed in a program
+    ///
+    /// # Arguments
+    /// * `n`: number of instructions
+    ///
+    /// # Return
+    /// `Self`
+    pub fn max_length(&mut self, n: usize) -> &mut Self
+    {
+        self.max_length = n;
+        self
+    }
+
+    /// Minimum distance between warriors
+    ///
+    /// # Arguments
+    /// * `n`: number of instructions
+    ///
+    /// # Return
+    /// `Self`
+    pub fn min_distance(&mut self, n: usize) -> &mut Self
+    {
+        self.min_distance = n;
+        self
+    }
+
+    /// Distance from the program counter instructions can read at
+    ///
+    /// # Arguments
+    /// * `n`: number of instructions
+    ///
+    /// # Return
+    /// `Self`
+    pub fn read_limit(&mut self, n: usize) -> &mut Self
+    {
+        self.read_limit = Some(n);
+        self
+    }
+
+    /// Distance from the program counter instructions can write at
+    ///
+    /// # Arguments
+    /// * `n`: number of instructions
+    ///
+    /// # Return
+    /// `Self`
+    pub fn write_limit(&mut self, n: usize) -> &mut Self
+    {
+        self.write_limit = Some(n);
+        self
+    }
+
+    /// Mars version multiplied by 100 (e.g. version 0.8 -> 80)
+    ///
+    /// # Arguments
+    /// * `version`: version number
+    ///
+    /// # Return
+    /// `Self`
+    pub fn version(&mut self, version: usize) -> &mut Self
+    {
+        self.version = version;
+        self
+    }
+
+    /// Number of warriors in each round
+    ///
+    /// # Arguments
+    /// * `n`: number of warriors
+    ///
+    /// # Return
+    /// `Self`
+    pub fn warriors(&mut self, n: usize) -> &mut Self
+    {
+        self.warriors = n;
+        self
+    }
+
+    /// Number of rounds in the match
+    ///
+    /// # Arguments
+    /// * `n`: number of rounds
+    ///
+    /// # Return
+    /// `Self`
+    pub fn rounds(&mut self, n: usize) -> &mut Self
+    {
+        self.rounds = n;
+        self
+    }
+
+    /// Standard deciding the modifier of instructions written without one
+    ///
+    /// # Arguments
+    /// * `standard`: redcode standard
+    ///
+    /// # Return
+    /// `Self`
+    pub fn standard(&mut self, standard: Standard) -> &mut Self
+    {
+        self.standard = standard;
+        self
+    }
+
+    /// Modifier an instruction written without one takes under the standard
+    ///
+    /// # Arguments
+    /// * `op`: opcode of the instruction
+    /// * `a_mode`: addressing mode of the A field
+    /// * `b_mode`: addressing mode of the B field
+    pub(super) fn default_modifier(
+        &self,
+        op:     OpCode,
+        a_mode: AddressingMode,
+        b_mode: AddressingMode,
+        ) -> Modifier
+    {
+        self.standard.default_modifier(op, a_mode, b_mode)
+    }
+
+    /// Get the value of a predefined constant
+    ///
+    /// # Arguments
+    /// * `name`: name of the constant, e.g. `CORESIZE`
+    ///
+    /// # Return
+    /// value of the constant if `name` is one
+    pub(super) fn constant(&self, name: &str) -> Option<i64>
+    {
+        let value = match name {
+            "CORESIZE"     => self.core_size,
+            "PSPACESIZE"   => self.pspace_size,
+            "MAXCYCLES"    => self.max_cycles,
+            "MAXPROCESSES" => self.max_processes,
+            "MAXLENGTH"    => self.max_length,
+            "MINDISTANCE"  => self.min_distance,
+            "READLIMIT"    => self.read_limit.unwrap_or(self.core_size),
+            "WRITELIMIT"   => self.write_limit.unwrap_or(self.core_size),
+            "VERSION"      => self.version,
+            "WARRIORS"     => self.warriors,
+            "ROUNDS"       => self.rounds,
+            _              => return None,
+        };
+        Some(value as i64)
+    }
+
+    /// Reduce a value modulo the core size into the range
+    /// `-CORESIZE/2..=CORESIZE/2`
+    ///
+    /// # Arguments
+    /// * `value`: value to reduce
+    pub(super) fn fold(&self, value: i64) -> i64
+    {
+        let size = self.core_size as i64;
+        if size == 0 {
+            return value;
+        }
+
+        let value = value.rem_euclid(size);
+        if value > size / 2 {
+            value - size
+        } else {
+            value
+        }
+    }
+}
+
+impl Default for Environment
+{
+    fn default() -> Self
+    {
+        Self::new()
+    }
+}
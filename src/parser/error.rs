@@ -0,0 +1,258 @@
+//! Errors reported while parsing
+
+use std::error::Error;
+use std::fmt;
+
+use super::lexer::Token;
+
+/// Structure containing all data about an error occuring during parsing
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError
+{
+    kind:   ParseErrorKind,
+    line:   usize,
+    column: usize,
+    start:  usize,
+    end:    usize,
+}
+
+/// How serious a diagnostic is
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Severity
+{
+    /// The program could not be assembled correctly
+    Error,
+
+    /// The program was assembled but may not mean what was written
+    Warning,
+}
+
+/// Kinds of errors the parser can throw
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseErrorKind
+{
+    /// A character that cannot begin any token
+    UnexpectedCharacter,
+
+    /// A token that is not valid where it appears
+    UnexpectedToken,
+
+    /// An operation code that does not exist
+    UnknownOpcode,
+
+    /// A modifier that does not exist
+    UnknownModifier,
+
+    /// An instruction is missing an operand
+    MissingOperand,
+
+    /// An instruction has more than two operands
+    TooManyOperands,
+
+    /// An expression ended before it was complete
+    ExpectedExpression,
+
+    /// A `(` without a matching `)`
+    UnbalancedParenthesis,
+
+    /// A number or expression does not fit in a field
+    ValueOutOfRange,
+
+    /// A symbol that was never defined
+    UndefinedLabel(String),
+
+    /// A symbol that was defined more than once
+    DuplicateLabel(String),
+
+    /// An `EQU` continuation line without a preceding `EQU`
+    MissingEquateName,
+
+    /// An equate whose definition refers to itself
+    RecursiveEquate(String),
+
+    /// Division or modulo by zero while evaluating an expression
+    DivisionByZeroInExpression,
+
+    /// A `FOR` without a matching `ROF`
+    UnmatchedFor,
+
+    /// A `ROF` without a matching `FOR`
+    UnmatchedRof,
+
+    /// The expression of an `;assert` comment is false
+    AssertionFailed,
+
+    /// Text following `END` that is ignored (warning)
+    TextAfterEnd,
+
+    /// The start of the program is given more than once, the last one is
+    /// used (warning)
+    StartRedefined,
+}
+
+impl ParseErrorKind
+{
+    /// How serious an error of this kind is
+    pub fn severity(&self) -> Severity
+    {
+        match *self {
+            ParseErrorKind::TextAfterEnd
+                | ParseErrorKind::StartRedefined => Severity::Warning,
+            _                                    => Severity::Error,
+        }
+    }
+}
+
+impl ParseError
+{
+    /// Create an error covering bytes `start..end` of the input
+    pub(super) fn new(kind: ParseErrorKind, start: usize, end: usize) -> Self
+    {
+        ParseError { kind, line: 0, column: 0, start, end }
+    }
+
+    /// Create an error covering a token
+    pub(super) fn at(kind: ParseErrorKind, token: &Token) -> Self
+    {
+        Self::new(kind, token.start, token.end)
+    }
+
+    /// Create an error covering the position just after a token
+    pub(super) fn after(kind: ParseErrorKind, token: &Token) -> Self
+    {
+        Self::new(kind, token.end, token.end)
+    }
+
+    /// Move the error to a different place in the input
+    ///
+    /// # Arguments
+    /// * `f`: maps a byte offset to its new position
+    pub(super) fn map_span<F>(mut self, f: F) -> Self
+        where F: Fn(usize) -> usize
+    {
+        self.start = f(self.start);
+        self.end   = f(self.end).max(self.start);
+        self
+    }
+
+    /// Fill in the line and column of the error from the program text. The
+    /// span is moved back to the start of the characters it falls in
+    pub(super) fn locate(mut self, program_str: &str) -> Self
+    {
+        self.start = char_boundary(program_str, self.start);
+        self.end   = char_boundary(program_str, self.end).max(self.start);
+
+        let before = &program_str[..self.start];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+
+        self.line   = before.matches('\n').count() + 1;
+        self.column = before[line_start..].chars().count() + 1;
+        self
+    }
+
+    /// Kind of error
+    pub fn kind(&self) -> &ParseErrorKind
+    {
+        &self.kind
+    }
+
+    /// Line the error occurred on, starting at `1`
+    pub fn line(&self) -> usize
+    {
+        self.line
+    }
+
+    /// Column the error occurred at, starting at `1`
+    pub fn column(&self) -> usize
+    {
+        self.column
+    }
+
+    /// Byte range of the input the error covers
+    pub fn span(&self) -> (usize, usize)
+    {
+        (self.start, self.end)
+    }
+
+    /// How serious the error is
+    pub fn severity(&self) -> Severity
+    {
+        self.kind.severity()
+    }
+}
+
+/// Closest offset at or before `offset` that starts a character of the text
+///
+/// # Arguments
+/// * `text`: text the offset points into
+/// * `offset`: byte offset, possibly past the end of the text
+fn char_boundary(text: &str, offset: usize) -> usize
+{
+    let mut offset = offset.min(text.len());
+    while !text.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    offset
+}
+
+impl fmt::Display for ParseError
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        match self.severity() {
+            Severity::Error   =>
+                write!(f, "{}:{}: {}", self.line, self.column, self.kind),
+            Severity::Warning =>
+                write!(f, "{}:{}: warning: {}", self.line, self.column, self.kind),
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+impl fmt::Display for ParseErrorKind
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        match *self {
+            ParseErrorKind::UnexpectedCharacter =>
+                write!(f, "unexpected character"),
+            ParseErrorKind::UnexpectedToken =>
+                write!(f, "unexpected token"),
+            ParseErrorKind::UnknownOpcode =>
+                write!(f, "unknown opcode"),
+            ParseErrorKind::UnknownModifier =>
+                write!(f, "unknown modifier"),
+            ParseErrorKind::MissingOperand =>
+                write!(f, "missing operand"),
+            ParseErrorKind::TooManyOperands =>
+                write!(f, "too many operands"),
+            ParseErrorKind::ExpectedExpression =>
+                write!(f, "expected an expression"),
+            ParseErrorKind::UnbalancedParenthesis =>
+                write!(f, "unbalanced parenthesis"),
+            ParseErrorKind::ValueOutOfRange =>
+                write!(f, "value out of range"),
+            ParseErrorKind::UndefinedLabel(ref name) =>
+                write!(f, "undefined label `{}`", name),
+            ParseErrorKind::DuplicateLabel(ref name) =>
+                write!(f, "duplicate label `{}`", name),
+            ParseErrorKind::MissingEquateName =>
+                write!(f, "`EQU` without a name"),
+            ParseErrorKind::RecursiveEquate(ref name) =>
+                write!(f, "equate `{}` refers to itself", name),
+            ParseErrorKind::DivisionByZeroInExpression =>
+                write!(f, "division by zero in expression"),
+            ParseErrorKind::UnmatchedFor =>
+                write!(f, "`FOR` without a matching `ROF`"),
+            ParseErrorKind::UnmatchedRof =>
+                write!(f, "`ROF` without a matching `FOR`"),
+            ParseErrorKind::AssertionFailed =>
+                write!(f, "assertion failed"),
+            ParseErrorKind::TextAfterEnd =>
+                write!(f, "text after `END` is ignored"),
+            ParseErrorKind::StartRedefined =>
+                write!(f, "start redefined, the last `ORG` or `END` is used"),
+        }
+    }
+}
@@ -0,0 +1,237 @@
+//! Evaluation of integer expressions appearing in redcode operands
+
+use super::{ParseResult, ParseError, ParseErrorKind};
+use super::lexer::{Token, TokenKind};
+
+/// Holds state for evaluating an expression
+struct Evaluator<'t, 'a: 't, F>
+    where F: Fn(&Token<'a>) -> ParseResult<i64>
+{
+    tokens: &'t [Token<'a>],
+    pos:    usize,
+    lookup: F,
+}
+
+impl<'t, 'a, F> Evaluator<'t, 'a, F>
+    where F: Fn(&Token<'a>) -> ParseResult<i64>
+{
+    /// Create an error at the current position
+    fn error(&self, kind: ParseErrorKind) -> ParseError
+    {
+        match self.tokens.get(self.pos) {
+            Some(token) => ParseError::at(kind, token),
+            None        => ParseError::after(kind, self.tokens.last().unwrap()),
+        }
+    }
+
+    /// Get the next token without consuming it
+    fn peek(&self) -> Option<&'a str>
+    {
+        self.tokens.get(self.pos).map(|t| t.content)
+    }
+
+    /// Consume the next token if its content is one of `ops`
+    fn accept(&mut self, ops: &[&str]) -> Option<&'a str>
+    {
+        match self.peek() {
+            Some(op) if ops.contains(&op) => {
+                self.pos += 1;
+                Some(op)
+            }
+            _ => None
+        }
+    }
+
+    /// `expr := and ('||' and)*`
+    fn or(&mut self) -> ParseResult<i64>
+    {
+        let mut lhs = self.and()?;
+        while self.accept(&["||"]).is_some() {
+            let rhs = self.and()?;
+            lhs = (lhs != 0 || rhs != 0) as i64;
+        }
+        Ok(lhs)
+    }
+
+    /// `and := comparison ('&&' comparison)*`
+    fn and(&mut self) -> ParseResult<i64>
+    {
+        let mut lhs = self.comparison()?;
+        while self.accept(&["&&"]).is_some() {
+            let rhs = self.comparison()?;
+            lhs = (lhs != 0 && rhs != 0) as i64;
+        }
+        Ok(lhs)
+    }
+
+    /// `comparison := sum (('==' | '!=' | '<' | '>' | '<=' | '>=') sum)*`
+    fn comparison(&mut self) -> ParseResult<i64>
+    {
+        let mut lhs = self.sum()?;
+        while let Some(op) = self.accept(&["==", "!=", "<", ">", "<=", ">="]) {
+            let rhs = self.sum()?;
+            lhs = match op {
+                "==" => lhs == rhs,
+                "!=" => lhs != rhs,
+                "<"  => lhs < rhs,
+                ">"  => lhs > rhs,
+                "<=" => lhs <= rhs,
+                _    => lhs >= rhs,
+            } as i64;
+        }
+        Ok(lhs)
+    }
+
+    /// `sum := product (('+' | '-') product)*`
+    fn sum(&mut self) -> ParseResult<i64>
+    {
+        let mut lhs = self.product()?;
+        while let Some(op) = self.accept(&["+", "-"]) {
+            let rhs = self.product()?;
+            lhs = if op == "+" {
+                lhs.wrapping_add(rhs)
+            } else {
+                lhs.wrapping_sub(rhs)
+            };
+        }
+        Ok(lhs)
+    }
+
+    /// `product := unary (('*' | '/' | '%') unary)*`
+    fn product(&mut self) -> ParseResult<i64>
+    {
+        let mut lhs = self.unary()?;
+        while let Some(op) = self.accept(&["*", "/", "%"]) {
+            let op_token = self.tokens[self.pos - 1];
+            let rhs = self.unary()?;
+            lhs = match op {
+                "*" => lhs.wrapping_mul(rhs),
+                _ if rhs == 0 => return Err(ParseError::at(
+                    ParseErrorKind::DivisionByZeroInExpression,
+                    &op_token
+                    )),
+                "/" => lhs.wrapping_div(rhs),
+                _   => lhs.wrapping_rem(rhs),
+            };
+        }
+        Ok(lhs)
+    }
+
+    /// `unary := ('-' | '+' | '!') unary | primary`
+    fn unary(&mut self) -> ParseResult<i64>
+    {
+        match self.accept(&["-", "+", "!"]) {
+            Some("-") => Ok(self.unary()?.wrapping_neg()),
+            Some("!") => Ok((self.unary()? == 0) as i64),
+            Some(_)   => self.unary(),
+            None      => self.primary(),
+        }
+    }
+
+    /// `primary := number | symbol | '(' expr ')'`
+    fn primary(&mut self) -> ParseResult<i64>
+    {
+        let token = match self.tokens.get(self.pos) {
+            Some(token) => *token,
+            None        => {
+                return Err(self.error(ParseErrorKind::ExpectedExpression));
+            }
+        };
+        self.pos += 1;
+
+        match token.kind {
+            TokenKind::Number => token.content.parse().map_err(|_| {
+                ParseError::at(ParseErrorKind::ValueOutOfRange, &token)
+            }),
+            TokenKind::Label
+                | TokenKind::Identifier => (self.lookup)(&token),
+            _ if token.content == "(" => {
+                let value = self.or()?;
+                self.accept(&[")"]).ok_or_else(|| {
+                    ParseError::at(ParseErrorKind::UnbalancedParenthesis, &token)
+                })?;
+                Ok(value)
+            }
+            _ => Err(ParseError::at(ParseErrorKind::UnexpectedToken, &token)),
+        }
+    }
+}
+
+/// Evaluate an expression
+///
+/// # Arguments
+/// * `tokens`: tokens making up the expression, all of which must be consumed,
+///   cannot be empty
+/// * `lookup`: resolves a symbol to its value
+///
+/// # Return
+/// value of the expression on success `ParseError` otherwise
+pub(super) fn evaluate<'a, F>(tokens: &[Token<'a>], lookup: F)
+    -> ParseResult<i64>
+    where F: Fn(&Token<'a>) -> ParseResult<i64>
+{
+    let mut evaluator = Evaluator { tokens, pos: 0, lookup };
+    let value = evaluator.or()?;
+
+    if evaluator.pos == tokens.len() {
+        Ok(value)
+    } else {
+        Err(evaluator.error(ParseErrorKind::UnexpectedToken))
+    }
+}
+
+#[cfg(test)]
+mod test
+{
+    use super::*;
+    use super::super::lexer::lex_into;
+
+    fn eval(expr: &str) -> ParseResult<i64>
+    {
+        let (mut tokens, mut errors) = (vec![], vec![]);
+        lex_into(expr, &mut tokens, &mut errors);
+        evaluate(&tokens, |sym| if sym.content == "x" {
+            Ok(7)
+        } else {
+            Err(ParseError::at(
+                ParseErrorKind::UndefinedLabel(sym.content.to_owned()),
+                sym
+                ))
+        })
+    }
+
+    fn eval_err(expr: &str) -> (ParseErrorKind, (usize, usize))
+    {
+        let err = eval(expr).unwrap_err();
+        (err.kind().clone(), err.span())
+    }
+
+    #[test]
+    fn test_precedence()
+    {
+        assert_eq!(Ok(14), eval("2 + 3 * 4"));
+        assert_eq!(Ok(20), eval("(2 + 3) * 4"));
+        assert_eq!(Ok(1),  eval("-7 % 4 + 4"));
+        assert_eq!(Ok(-2), eval("x - 9"));
+    }
+
+    #[test]
+    fn test_comparison_and_logic()
+    {
+        assert_eq!(Ok(1), eval("x == 7 && 2 < 3"));
+        assert_eq!(Ok(0), eval("!(x >= 7) || 3 <= 2"));
+        assert_eq!(Ok(1), eval("x != 6"));
+    }
+
+    #[test]
+    fn test_errors()
+    {
+        use super::ParseErrorKind::*;
+
+        assert_eq!((DivisionByZeroInExpression, (2, 3)), eval_err("1 / 0"));
+        assert_eq!((UndefinedLabel("y".to_owned()), (0, 1)), eval_err("y + 1"));
+        assert_eq!((UnbalancedParenthesis, (0, 1)), eval_err("(1 + 2"));
+        assert_eq!((UnexpectedToken, (2, 3)), eval_err("1 2"));
+        assert_eq!((ExpectedExpression, (3, 3)), eval_err("1 +"));
+    }
+}
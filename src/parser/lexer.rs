@@ -0,0 +1,378 @@
+//! Conversion of program text into a stream of `Token`s
+
+use regex::Regex;
+
+use super::{ParseError, ParseErrorKind};
+
+lazy_static! {
+    /// Matches a single token at the beginning of the input
+    static ref TOKEN_RE: Regex = Regex::new(concat!(
+        r"^(?:",
+        r"(?P<space>[ \t\r]+)",
+        r"|(?P<comment>;[^\n]*)",
+        r"|(?P<newline>\n)",
+        r"|(?P<ident>[A-Za-z_][A-Za-z0-9_]*)",
+        r"|(?P<number>[0-9]+)",
+        r"|(?P<symbol>==|!=|<=|>=|&&|\|\||[-+*/%()<>!=,.:&#$@{}])",
+        r")"
+        )).unwrap();
+}
+
+/// Operation codes recognized by the lexer
+const OPCODES: &[&str] = &[
+    "dat", "mov", "add", "sub", "mul", "div", "mod", "jmp", "jmz", "jmn",
+    "djn", "spl", "seq", "cmp", "sne", "slt", "ldp", "stp", "nop"
+];
+
+/// Pseudo-operations recognized by the lexer
+const PSEUDO_OPS: &[&str] = &["equ", "org", "end", "for", "rof", "pin"];
+
+/// Characters denoting an addressing mode when they begin an operand
+const MODE_SYMBOLS: &[&str] = &["#", "$", "@", "*", "<", ">", "{", "}"];
+
+/// Unit of information from an input program
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(super) struct Token<'a>
+{
+    /// Text of the token
+    pub content: &'a str,
+
+    /// Byte offset of the first character of the token
+    pub start:   usize,
+
+    /// Byte offset one past the last character of the token
+    pub end:     usize,
+
+    /// Type of token
+    pub kind:    TokenKind,
+}
+
+/// Type of token
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(super) enum TokenKind
+{
+    /// Jump label
+    Label,
+
+    /// "MOV", "DAT" ...
+    OpCode,
+
+    /// "A", "B", ...
+    OpMode,
+
+    /// "$", "#" ...
+    AddressingMode,
+
+    /// "+", "-" ...
+    Symbol,
+
+    /// Number Literaly
+    Number,
+
+    /// String literal
+    Identifier,
+
+    /// "EQU", "ORG", "END" ...
+    PseudoOp,
+
+    /// Text following a ";"
+    Comment,
+
+    /// End of a line
+    Newline,
+
+    /// Rest of a line from a character that cannot be lexed
+    Invalid,
+}
+
+/// Holds state for lexing
+struct Lexer<'a, 'b>
+    where 'a: 'b
+{
+    input:         &'a str,
+    output:        &'b mut Vec<Token<'a>>,
+    errors:        &'b mut Vec<ParseError>,
+
+    /// Byte offset of the next unread character
+    pos:           usize,
+
+    /// Only labels have been read on the current line
+    line_start:    bool,
+
+    /// The next token begins an operand
+    operand_start: bool,
+
+    /// The next token is an instruction modifier
+    modifier_next: bool,
+}
+
+impl<'a, 'b> Lexer<'a, 'b>
+{
+    /// Read tokens until the input is exhausted
+    fn run(&mut self)
+    {
+        while self.pos < self.input.len() {
+            let rest = &self.input[self.pos..];
+
+            let caps = match TOKEN_RE.captures(rest) {
+                Some(caps) => caps,
+                None       => {
+                    self.invalid();
+                    continue;
+                }
+            };
+
+            let (group, m) = ["space", "comment", "newline", "ident", "number",
+                "symbol"]
+                .iter()
+                .filter_map(|name| caps.name(name).map(|m| (*name, m)))
+                .next()
+                .unwrap();
+
+            let (start, end) = (self.pos + m.start(), self.pos + m.end());
+            self.pos = end;
+
+            let kind = match group {
+                "space"   => continue,
+                "comment" => TokenKind::Comment,
+                "newline" => self.newline(),
+                "ident"   => self.ident(m.as_str()),
+                "number"  => self.number(),
+                _         => self.symbol(m.as_str()),
+            };
+
+            self.output.push(Token {
+                content: &self.input[start..end],
+                start,
+                end,
+                kind,
+            });
+        }
+
+    }
+
+    /// Report the unexpected character at the current position and read the
+    /// rest of its line as a single `Invalid` token, so that the parser can
+    /// keep the labels of the line and a placeholder for its instruction
+    fn invalid(&mut self)
+    {
+        let rest = &self.input[self.pos..];
+        let len = rest.chars().next().map_or(1, |c| c.len_utf8());
+        let start = self.pos;
+
+        self.errors.push(ParseError::new(
+            ParseErrorKind::UnexpectedCharacter,
+            start,
+            start + len
+            ));
+
+        self.pos += rest.find('\n').unwrap_or(rest.len());
+        self.output.push(Token {
+            content: &self.input[start..self.pos],
+            start,
+            end:     self.pos,
+            kind:    TokenKind::Invalid,
+        });
+    }
+
+    /// Classify a newline, resetting per-line state
+    fn newline(&mut self) -> TokenKind
+    {
+        self.line_start    = true;
+        self.operand_start = false;
+        self.modifier_next = false;
+        TokenKind::Newline
+    }
+
+    /// Classify an identifier by its content and position on the line
+    fn ident(&mut self, content: &str) -> TokenKind
+    {
+        let lower = content.to_lowercase();
+
+        if self.modifier_next {
+            self.modifier_next = false;
+            self.operand_start = true;
+            TokenKind::OpMode
+        } else if OPCODES.contains(&lower.as_str()) {
+            self.line_start    = false;
+            self.operand_start = true;
+            TokenKind::OpCode
+        } else if PSEUDO_OPS.contains(&lower.as_str()) {
+            self.line_start    = false;
+            self.operand_start = true;
+            TokenKind::PseudoOp
+        } else if self.line_start {
+            TokenKind::Label
+        } else {
+            self.operand_start = false;
+            TokenKind::Identifier
+        }
+    }
+
+    /// Classify a number literal
+    fn number(&mut self) -> TokenKind
+    {
+        self.line_start    = false;
+        self.operand_start = false;
+        TokenKind::Number
+    }
+
+    /// Classify a symbol by its content and position on the line
+    fn symbol(&mut self, content: &str) -> TokenKind
+    {
+        let follows_opcode = self.output.last()
+            .is_some_and(|t| t.kind == TokenKind::OpCode);
+
+        self.line_start = false;
+
+        if self.operand_start && MODE_SYMBOLS.contains(&content) {
+            self.operand_start = false;
+            TokenKind::AddressingMode
+        } else if content == "," {
+            self.operand_start = true;
+            TokenKind::Symbol
+        } else if content == "." && follows_opcode {
+            self.modifier_next = true;
+            TokenKind::Symbol
+        } else {
+            self.operand_start = false;
+            TokenKind::Symbol
+        }
+    }
+}
+
+/// Convert a string into `Token`s placing them in a buffer. The rest of a
+/// line from a character that cannot be lexed is read as an `Invalid` token
+///
+/// # Arguments
+/// * `program_str`: text of program
+/// * `buf`: buffer to place tokens in
+/// * `errors`: buffer to place errors in
+pub(super) fn lex_into<'a>(
+    program_str: &'a str,
+    buf:         &mut Vec<Token<'a>>,
+    errors:      &mut Vec<ParseError>,
+    )
+{
+    lex_range_into(program_str, 0, program_str.len(), buf, errors)
+}
+
+/// Convert part of a string into `Token`s placing them in a buffer. The spans
+/// of the tokens are offsets into the whole string
+///
+/// # Arguments
+/// * `program_str`: text of program
+/// * `start`: byte offset to start lexing at
+/// * `end`: byte offset to stop lexing at
+/// * `buf`: buffer to place tokens in
+/// * `errors`: buffer to place errors in
+pub(super) fn lex_range_into<'a>(
+    program_str: &'a str,
+    start:       usize,
+    end:         usize,
+    buf:         &mut Vec<Token<'a>>,
+    errors:      &mut Vec<ParseError>,
+    )
+{
+    Lexer {
+        input:         &program_str[..end],
+        output:        buf,
+        errors,
+        pos:           start,
+        line_start:    true,
+        operand_start: false,
+        modifier_next: false,
+    }.run()
+}
+
+#[cfg(test)]
+mod test
+{
+    use super::*;
+
+    fn lex<'a>(program_str: &'a str) -> Vec<Token<'a>>
+    {
+        let (mut buf, mut errors) = (vec![], vec![]);
+        lex_into(program_str, &mut buf, &mut errors);
+        assert!(errors.is_empty());
+        buf
+    }
+
+    fn kinds(program_str: &str) -> Vec<TokenKind>
+    {
+        lex(program_str).iter().map(|t| t.kind).collect()
+    }
+
+    #[test]
+    fn test_lex_instruction()
+    {
+        use self::TokenKind::*;
+
+        assert_eq!(
+            vec![Label, OpCode, Symbol, OpMode, AddressingMode, Number, Symbol,
+                AddressingMode, Identifier, Comment, Newline],
+            kinds("start MOV.AB #4, @start ; bomb\n")
+            );
+    }
+
+    #[test]
+    fn test_lex_spans()
+    {
+        let src = "  add.ab #4, 3";
+        let buf = lex(src);
+
+        for t in buf.iter() {
+            assert_eq!(t.content, &src[t.start..t.end]);
+        }
+
+        assert_eq!((2, 5), (buf[0].start, buf[0].end));
+        assert_eq!("ab", buf[2].content);
+    }
+
+    #[test]
+    fn test_lex_sigils_in_expressions_are_symbols()
+    {
+        use self::TokenKind::*;
+
+        assert_eq!(
+            vec![OpCode, AddressingMode, Number, Symbol, Number, Symbol,
+                AddressingMode, Identifier, Symbol, Number],
+            kinds("dat <1*2, >x<3")
+            );
+    }
+
+    #[test]
+    fn test_lex_pseudo_ops_and_operators()
+    {
+        use self::TokenKind::*;
+
+        assert_eq!(
+            vec![Label, PseudoOp, Symbol, Number, Symbol, Number, Symbol,
+                Symbol, Symbol, Number, Newline, PseudoOp, Identifier],
+            kinds("step EQU (2==3)&&!4\nEND step")
+            );
+    }
+
+    #[test]
+    fn test_lex_rejects_unknown_character()
+    {
+        let (mut buf, mut errors) = (vec![], vec![]);
+        lex_into("dat 0\nmov 0, 1 ? 2\ndat ?", &mut buf, &mut errors);
+
+        assert_eq!(2, errors.len());
+        assert_eq!(&ParseErrorKind::UnexpectedCharacter, errors[0].kind());
+        assert_eq!((15, 16), errors[0].span());
+        assert_eq!((23, 24), errors[1].span());
+
+        // the rest of an offending line is a single token
+        let kinds: Vec<_> = buf.iter().map(|t| t.kind).collect();
+        assert_eq!(
+            vec![TokenKind::OpCode, TokenKind::Number, TokenKind::Newline,
+                TokenKind::OpCode, TokenKind::Number, TokenKind::Symbol,
+                TokenKind::Number, TokenKind::Invalid, TokenKind::Newline,
+                TokenKind::OpCode, TokenKind::Invalid],
+            kinds
+            );
+        assert_eq!("? 2", buf[7].content);
+    }
+}
@@ -0,0 +1,1520 @@
+//! Tools for parsing strings into usable redcode instructions
+
+use super::redcode::traits::Instruction;
+use super::redcode::Warrior;
+use super::redcode::types::{
+    OpCode,
+    Modifier,
+    AddressingMode,
+    Address,
+    Value,
+    Pin
+};
+
+use std::collections::HashMap;
+
+mod lexer;
+use self::lexer::{Token, TokenKind, lex_into, lex_range_into};
+
+mod expression;
+use self::expression::evaluate;
+
+mod repetition;
+use self::repetition::unroll;
+
+mod error;
+pub use self::error::{ParseError, ParseErrorKind, Severity};
+
+mod environment;
+pub use self::environment::Environment;
+
+/// Result of a parse
+pub type ParseResult<T> = Result<T, ParseError>;
+
+/// A line of tokens with its comments removed
+type Line<'a> = Vec<Token<'a>>;
+
+/// Meaning bound to a name in the symbol table
+#[derive(Debug, Clone)]
+enum Symbol<'a>
+{
+    /// Index of the instruction the label is attached to
+    Label(usize),
+
+    /// Lines of tokens substituted wherever the name appears
+    Equate(Vec<Line<'a>>),
+}
+
+/// Holds state for parsing
+struct Parser<'a, 'b, T: 'b>
+where T: Instruction
+{
+    sym_table: HashMap<String, Symbol<'a>>,
+    source:    &'a str,
+    input:     &'b [Token<'a>],
+    output:    &'b mut Warrior<T>,
+    errors:    &'b mut Vec<ParseError>,
+    env:       &'b Environment,
+    origin:    Option<Line<'a>>,
+    pin:       Option<Line<'a>>,
+    asserts:   Vec<Line<'a>>,
+}
+
+impl<'a, 'b, T> Parser<'a, 'b, T>
+where T: Instruction
+{
+    /// Create a parser with an empty symbol table
+    ///
+    /// # Arguments
+    /// * `source`: text of program
+    /// * `input`: tokens of the text
+    /// * `output`: warrior to place parsed data in
+    /// * `errors`: buffer to place errors in
+    /// * `env`: settings of the `Mars` the program is assembled for
+    fn new(
+        source: &'a str,
+        input:  &'b [Token<'a>],
+        output: &'b mut Warrior<T>,
+        errors: &'b mut Vec<ParseError>,
+        env:    &'b Environment,
+        ) -> Self
+    {
+        Parser {
+            sym_table: HashMap::new(),
+            source,
+            input,
+            output,
+            errors,
+            env,
+            origin:    None,
+            pin:       None,
+            asserts:   vec![],
+        }
+    }
+
+    /// Assemble the input tokens into the output buffer, recording errors and
+    /// continuing with the next line whenever one is found
+    fn run(&mut self)
+    {
+        let lines = self.collect_equates();
+
+        let mut expanded = vec![];
+        for line in lines {
+            let mut buf = vec![];
+            match self.expand_into(line, &mut vec![], &mut buf) {
+                Ok(())   => expanded.append(&mut buf),
+                Err(err) => self.errors.push(err),
+            }
+        }
+
+        let statements = self.collect_labels(expanded);
+        for (i, statement) in statements.iter().enumerate() {
+            // a placeholder keeps the offsets of later instructions intact.
+            // Empty statements and invalid text were reported already
+            let instr = if statement.is_empty() || has_invalid(statement) {
+                T::default()
+            } else {
+                self.assemble(i, statement).unwrap_or_else(|err| {
+                    self.errors.push(err);
+                    T::default()
+                })
+            };
+            self.output.instructions_mut().push(instr);
+        }
+
+        if let Err(err) = self.resolve_start() {
+            self.errors.push(err);
+        }
+
+        if let Err(err) = self.resolve_pin() {
+            self.errors.push(err);
+        }
+
+        for assert in self.asserts.clone() {
+            if let Err(err) = self.check_assert(&assert) {
+                self.errors.push(err);
+            }
+        }
+    }
+
+    /// Set the start of the output from the last `ORG` or `END` operand,
+    /// falling back to the label `start` and then the first instruction
+    fn resolve_start(&mut self) -> ParseResult<()>
+    {
+        let (expr, start) = match self.origin {
+            Some(ref expr) => (expr, self.evaluate_absolute(expr)?),
+            None => match self.sym_table.get("start") {
+                Some(&Symbol::Label(target)) => {
+                    self.output.set_start(target as Address);
+                    return Ok(());
+                }
+                _ => return Ok(()),
+            },
+        };
+
+        if start < 0 || start as usize >= self.output.len() {
+            return Err(ParseError::new(
+                ParseErrorKind::ValueOutOfRange,
+                expr[0].start,
+                expr[expr.len() - 1].end
+                ));
+        }
+
+        self.output.set_start(start as Address);
+        Ok(())
+    }
+
+    /// Set the PIN of the output from the last `PIN` operand
+    fn resolve_pin(&mut self) -> ParseResult<()>
+    {
+        let expr = match self.pin {
+            Some(ref expr) => expr,
+            None           => return Ok(()),
+        };
+
+        let pin = self.evaluate_absolute(expr)?;
+        if pin < 0 || pin > Pin::MAX as i64 {
+            return Err(ParseError::new(
+                ParseErrorKind::ValueOutOfRange,
+                expr[0].start,
+                expr[expr.len() - 1].end
+                ));
+        }
+
+        self.output.set_pin(Some(pin as Pin));
+        Ok(())
+    }
+
+    /// Check that the expression of an `;assert` comment holds in the
+    /// environment
+    ///
+    /// # Arguments
+    /// * `expr`: tokens of the expression
+    fn check_assert(&self, expr: &[Token<'a>]) -> ParseResult<()>
+    {
+        let (start, end) = (expr[0].start, expr[expr.len() - 1].end);
+
+        if self.evaluate_expanded(expr)? == 0 {
+            return Err(ParseError::new(
+                ParseErrorKind::AssertionFailed,
+                start,
+                end
+                ));
+        }
+
+        Ok(())
+    }
+
+    /// Evaluate an expression appearing outside of an instruction, where
+    /// labels are offsets from the first instruction
+    ///
+    /// # Arguments
+    /// * `expr`: tokens of the expression
+    fn evaluate_absolute(&self, expr: &[Token<'a>]) -> ParseResult<i64>
+    {
+        evaluate(expr, |name| self.lookup(name, 0))
+    }
+
+    /// Substitute equates in an expression appearing outside of an
+    /// instruction and evaluate it
+    ///
+    /// # Arguments
+    /// * `expr`: tokens of the expression, cannot be empty
+    fn evaluate_expanded(&self, expr: &[Token<'a>]) -> ParseResult<i64>
+    {
+        let mut lines = vec![];
+        self.expand_into(expr.to_vec(), &mut vec![], &mut lines)?;
+        let expanded: Line = lines.into_iter().flatten().collect();
+
+        if expanded.is_empty() {
+            return Err(ParseError::at(
+                ParseErrorKind::ExpectedExpression,
+                &expr[0]
+                ));
+        }
+
+        self.evaluate_absolute(&expanded)
+    }
+
+    /// Split the input into lines, stopping after an `END` line
+    fn lines(&mut self) -> Vec<Line<'a>>
+    {
+        let mut lines = vec![];
+        let mut raw_lines = self.input.split(|t| t.kind == TokenKind::Newline);
+
+        for raw in raw_lines.by_ref() {
+            for token in raw.iter().filter(|t| t.kind == TokenKind::Comment) {
+                if self.comment(token) {
+                    // everything before a `;redcode` line is ignored
+                    lines.clear();
+                    self.clear_metadata();
+                }
+            }
+
+            let line: Line = raw.iter()
+                .filter(|t| t.kind != TokenKind::Comment)
+                .cloned()
+                .collect();
+
+            let is_end = line.iter()
+                .find(|t| !is_ident(t))
+                .is_some_and(|t| is_pseudo_op(t, "end"));
+
+            lines.push(line);
+
+            if is_end {
+                break;
+            }
+        }
+
+        let ignored = raw_lines.flat_map(|raw| raw.iter())
+            .find(|t| t.kind != TokenKind::Comment);
+
+        if let Some(token) = ignored {
+            self.errors.push(ParseError::at(ParseErrorKind::TextAfterEnd, token));
+        }
+
+        lines
+    }
+
+    /// Read the metadata held in a comment
+    ///
+    /// # Arguments
+    /// * `token`: comment to read
+    ///
+    /// # Return
+    /// `true` if the comment is a `;redcode` line
+    fn comment(&mut self, token: &Token<'a>) -> bool
+    {
+        let text = token.content;
+        let keyword_start = text.len() - text[1..].trim_start().len();
+        let keyword_end = text[keyword_start..]
+            .find(char::is_whitespace)
+            .map_or(text.len(), |i| keyword_start + i);
+        let value = text[keyword_end..].trim();
+
+        match text[keyword_start..keyword_end].to_lowercase().as_str() {
+            "name"     => self.output.set_name(Some(value.to_owned())),
+            "author"   => self.output.set_author(Some(value.to_owned())),
+            "version"  => self.output.set_version(Some(value.to_owned())),
+            "strategy" => self.output.strategy_mut().push(value.to_owned()),
+            "assert"   => {
+                self.output.asserts_mut().push(value.to_owned());
+
+                let mut expr = vec![];
+                let error_count = self.errors.len();
+                lex_range_into(
+                    self.source,
+                    token.start + keyword_end,
+                    token.end,
+                    &mut expr,
+                    self.errors
+                    );
+
+                if !expr.is_empty() {
+                    self.asserts.push(expr);
+                } else if self.errors.len() == error_count {
+                    self.errors.push(ParseError::new(
+                        ParseErrorKind::ExpectedExpression,
+                        token.start + keyword_end,
+                        token.start + keyword_end
+                        ));
+                }
+            }
+            keyword if keyword.starts_with("redcode") => {
+                let rest = &text[keyword_start + "redcode".len()..];
+                self.output.set_redcode(Some(rest.trim_end().to_owned()));
+                return true;
+            }
+            _          => {}
+        }
+
+        false
+    }
+
+    /// Forget the metadata read so far
+    fn clear_metadata(&mut self)
+    {
+        self.output.set_name(None);
+        self.output.set_author(None);
+        self.output.set_version(None);
+        self.output.strategy_mut().clear();
+        self.output.asserts_mut().clear();
+        self.asserts.clear();
+    }
+
+    /// Record every `EQU` definition in the symbol table
+    ///
+    /// # Return
+    /// the remaining lines of the program
+    fn collect_equates(&mut self) -> Vec<Line<'a>>
+    {
+        let mut lines = vec![];
+        let mut last_names: Vec<&'a str> = vec![];
+
+        for line in self.lines() {
+            match line.iter().position(|t| is_pseudo_op(t, "equ")) {
+                Some(k) => {
+                    if let Err(err) = self.collect_equate(&line, k, &mut last_names) {
+                        self.errors.push(err);
+                    }
+                }
+                None    => {
+                    if !line.is_empty() {
+                        last_names.clear();
+                        lines.push(line);
+                    }
+                }
+            }
+        }
+
+        lines
+    }
+
+    /// Record the `EQU` definition on a line
+    ///
+    /// # Arguments
+    /// * `line`: line containing the definition
+    /// * `k`: index of the `EQU` token
+    /// * `last_names`: names defined by the previous line, if it was an `EQU`
+    fn collect_equate(
+        &mut self,
+        line:       &[Token<'a>],
+        k:          usize,
+        last_names: &mut Vec<&'a str>,
+        ) -> ParseResult<()>
+    {
+        let (names, body) = (&line[..k], line[k + 1..].to_vec());
+
+        if let Some(t) = names.iter().find(|t| !is_ident(t)) {
+            last_names.clear();
+            return Err(ParseError::at(ParseErrorKind::UnexpectedToken, t));
+        }
+
+        if names.is_empty() {
+            // continuation of a multi-line equate
+            if last_names.is_empty() {
+                return Err(ParseError::at(
+                    ParseErrorKind::MissingEquateName,
+                    &line[k]
+                    ));
+            }
+
+            for name in last_names.iter() {
+                if let Some(&mut Symbol::Equate(ref mut v)) =
+                    self.sym_table.get_mut(*name)
+                {
+                    v.push(body.clone());
+                }
+            }
+        } else {
+            last_names.clear();
+            for name in names {
+                if self.define(name, Symbol::Equate(vec![body.clone()])) {
+                    last_names.push(name.content);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Substitute equates in a line, placing the resulting lines in a buffer
+    ///
+    /// # Arguments
+    /// * `line`: line to expand
+    /// * `stack`: names of the equates currently being expanded
+    /// * `buf`: buffer to place expanded lines in
+    fn expand_into(
+        &self,
+        line:  Line<'a>,
+        stack: &mut Vec<&'a str>,
+        buf:   &mut Vec<Line<'a>>,
+        ) -> ParseResult<()>
+    {
+        let found = line.iter().enumerate().filter(|&(_, t)| is_ident(t))
+            .filter_map(|(i, t)| match self.sym_table.get(t.content) {
+                Some(Symbol::Equate(body)) => Some((i, body)),
+                _                          => None,
+            })
+            .next();
+
+        let (i, body) = match found {
+            Some(found) => found,
+            None        => {
+                buf.push(line);
+                return Ok(());
+            }
+        };
+
+        let name = line[i].content;
+        if stack.contains(&name) {
+            return Err(ParseError::at(
+                ParseErrorKind::RecursiveEquate(name.to_owned()),
+                &line[i]
+                ));
+        }
+
+        // expand the body of the equate on its own
+        let mut lines = vec![];
+        stack.push(name);
+        for body_line in body.iter() {
+            self.expand_into(body_line.clone(), stack, &mut lines)?;
+        }
+        stack.pop();
+
+        if lines.is_empty() {
+            lines.push(vec![]);
+        }
+
+        // splice the expansion into the surrounding text
+        let mut first = line[..i].to_vec();
+        first.append(&mut lines[0]);
+        lines[0] = first;
+        lines.last_mut().unwrap().extend(line[i + 1..].iter().cloned());
+
+        let last = lines.pop().unwrap();
+        buf.extend(lines);
+        self.expand_into(last, stack, buf)
+    }
+
+    /// Record every label in the symbol table
+    ///
+    /// # Return
+    /// the remaining statements with their labels removed
+    fn collect_labels(&mut self, lines: Vec<Line<'a>>) -> Vec<Line<'a>>
+    {
+        let mut statements = vec![];
+        let mut pending = vec![];
+
+        for line in lines {
+            match self.collect_label(&line, &mut pending, &mut statements) {
+                Ok(true)  => break,
+                Ok(false) => {}
+                Err(err)  => {
+                    pending.clear();
+                    self.errors.push(err);
+                }
+            }
+        }
+
+        let index = statements.len();
+        for name in pending.drain(..) {
+            self.define(&name, Symbol::Label(index));
+        }
+
+        statements
+    }
+
+    /// Record the labels on a line
+    ///
+    /// # Arguments
+    /// * `line`: line to read
+    /// * `pending`: labels waiting for an instruction to attach to
+    /// * `statements`: buffer to place the line's statement in
+    ///
+    /// # Return
+    /// `true` if the line ends the program
+    fn collect_label(
+        &mut self,
+        line:       &[Token<'a>],
+        pending:    &mut Vec<Token<'a>>,
+        statements: &mut Vec<Line<'a>>,
+        ) -> ParseResult<bool>
+    {
+        let mut k = 0;
+        while k < line.len() && is_ident(&line[k]) {
+            pending.push(line[k]);
+            k += 1;
+
+            if line.get(k).is_some_and(|t| t.content == ":") {
+                k += 1;
+            }
+        }
+
+        let rest = &line[k..];
+        let first = match rest.first() {
+            Some(first) => first,
+            None        => return Ok(false),
+        };
+
+        match first.kind {
+            TokenKind::OpCode
+                | TokenKind::PseudoOp
+                | TokenKind::Invalid => {}
+            // a misspelled opcode reads as the last label of the line
+            _ if k > 0 => {
+                let opcode = pending.pop().unwrap();
+                self.errors.push(ParseError::at(
+                    ParseErrorKind::UnknownOpcode,
+                    &opcode
+                    ));
+            }
+            _ => return Err(ParseError::at(
+                ParseErrorKind::UnexpectedToken,
+                first
+                )),
+        }
+
+        let index = statements.len();
+        for name in pending.drain(..) {
+            self.define(&name, Symbol::Label(index));
+        }
+
+        match first.kind {
+            TokenKind::OpCode => statements.push(rest.to_vec()),
+            // the instruction could not be read, its place is kept empty
+            TokenKind::Invalid
+                | TokenKind::Symbol
+                | TokenKind::Number => statements.push(vec![]),
+            // an operand that could not be read was reported already
+            _ if has_invalid(rest) => return Ok(is_pseudo_op(first, "end")),
+            _ if is_pseudo_op(first, "end") => {
+                if rest.len() > 1 {
+                    self.set_origin(first, &rest[1..]);
+                }
+                return Ok(true);
+            }
+            _ if is_pseudo_op(first, "org") => {
+                if rest.len() == 1 {
+                    return Err(ParseError::after(
+                        ParseErrorKind::MissingOperand,
+                        first
+                        ));
+                }
+                self.set_origin(first, &rest[1..]);
+            }
+            _ if is_pseudo_op(first, "pin") => {
+                if rest.len() == 1 {
+                    return Err(ParseError::after(
+                        ParseErrorKind::MissingOperand,
+                        first
+                        ));
+                }
+                self.pin = Some(rest[1..].to_vec());
+            }
+            _ => return Err(ParseError::at(
+                ParseErrorKind::UnexpectedToken,
+                first
+                )),
+        }
+
+        Ok(false)
+    }
+
+    /// Record the expression giving the start of the program, warning if one
+    /// was already given
+    ///
+    /// # Arguments
+    /// * `token`: the `ORG` or `END` token
+    /// * `expr`: tokens of the expression
+    fn set_origin(&mut self, token: &Token<'a>, expr: &[Token<'a>])
+    {
+        if self.origin.is_some() {
+            self.errors.push(ParseError::at(
+                ParseErrorKind::StartRedefined,
+                token
+                ));
+        }
+
+        self.origin = Some(expr.to_vec());
+    }
+
+    /// Add a name to the symbol table, recording an error if it is already
+    /// defined
+    ///
+    /// # Arguments
+    /// * `name`: token naming the symbol
+    /// * `symbol`: meaning of the symbol
+    ///
+    /// # Return
+    /// `true` if the name was added
+    fn define(&mut self, name: &Token, symbol: Symbol<'a>) -> bool
+    {
+        if self.sym_table.contains_key(name.content) {
+            self.errors.push(ParseError::at(
+                ParseErrorKind::DuplicateLabel(name.content.to_owned()),
+                name
+                ));
+            return false;
+        }
+
+        self.sym_table.insert(name.content.to_owned(), symbol);
+        true
+    }
+
+    /// Get the value of a symbol as seen from an instruction, falling back to
+    /// the predefined constants of the environment
+    ///
+    /// # Arguments
+    /// * `name`: token naming the symbol
+    /// * `index`: index of the instruction the symbol appears in
+    fn lookup(&self, name: &Token, index: usize) -> ParseResult<i64>
+    {
+        if let Some(&Symbol::Label(target)) = self.sym_table.get(name.content) {
+            return Ok(target as i64 - index as i64);
+        }
+
+        match name.content {
+            "CURLINE" => Ok(index as i64),
+            _         => self.env.constant(name.content).ok_or_else(|| {
+                ParseError::at(
+                    ParseErrorKind::UndefinedLabel(name.content.to_owned()),
+                    name
+                    )
+            }),
+        }
+    }
+
+    /// Assemble a single statement into an instruction
+    ///
+    /// # Arguments
+    /// * `index`: index of the instruction in the program
+    /// * `statement`: tokens of the statement with labels removed
+    fn assemble(&self, index: usize, statement: &[Token<'a>])
+        -> ParseResult<T>
+    {
+        let op_token = &statement[0];
+        let op = parse_opcode(op_token.content).ok_or_else(|| {
+            ParseError::at(ParseErrorKind::UnknownOpcode, op_token)
+        })?;
+
+        let (modifier, rest) = if statement.get(1)
+            .is_some_and(|t| t.content == ".")
+        {
+            let token = statement.get(2).unwrap_or(&statement[1]);
+            let modifier = parse_modifier(token.content).ok_or_else(|| {
+                ParseError::at(ParseErrorKind::UnknownModifier, token)
+            })?;
+            (Some(modifier), &statement[3..])
+        } else {
+            (None, &statement[1..])
+        };
+
+        // each operand is paired with the token preceding it
+        let mut operands = vec![];
+        let mut prev = &statement[statement.len() - rest.len() - 1];
+        let mut start = 0;
+        for (i, token) in rest.iter().enumerate() {
+            if token.content == "," {
+                operands.push((prev, &rest[start..i]));
+                prev  = token;
+                start = i + 1;
+            }
+        }
+        operands.push((prev, &rest[start..]));
+
+        let lookup = |name: &Token<'a>| self.lookup(name, index);
+
+        let (a, b) = match operands.len() {
+            1 if rest.is_empty() => return Err(ParseError::after(
+                ParseErrorKind::MissingOperand,
+                prev
+                )),
+            1 if op == OpCode::Dat => (
+                (AddressingMode::Immediate, 0),
+                parse_operand(operands[0], &lookup, self.env)?,
+                ),
+            1 => (
+                parse_operand(operands[0], &lookup, self.env)?,
+                (AddressingMode::Direct, 0),
+                ),
+            2 => (
+                parse_operand(operands[0], &lookup, self.env)?,
+                parse_operand(operands[1], &lookup, self.env)?,
+                ),
+            _ => return Err(ParseError::at(
+                ParseErrorKind::TooManyOperands,
+                operands[2].0
+                )),
+        };
+
+        let mut instr = T::default();
+        instr.set_op(op);
+        instr.set_modifier(
+            modifier.unwrap_or_else(|| self.env.default_modifier(op, a.0, b.0))
+            );
+        instr.set_a_mode(a.0);
+        instr.set_a(a.1);
+        instr.set_b_mode(b.0);
+        instr.set_b(b.1);
+        Ok(instr)
+    }
+}
+
+/// Is the token a name that may be a label or an equate
+fn is_ident(token: &Token) -> bool
+{
+    token.kind == TokenKind::Label || token.kind == TokenKind::Identifier
+}
+
+/// Does a line hold text that could not be lexed
+fn has_invalid(line: &[Token]) -> bool
+{
+    line.iter().any(|t| t.kind == TokenKind::Invalid)
+}
+
+/// Is the token the pseudo-operation `name`
+fn is_pseudo_op(token: &Token, name: &str) -> bool
+{
+    token.kind == TokenKind::PseudoOp && token.content.eq_ignore_ascii_case(name)
+}
+
+/// Convert the text of an opcode into an `OpCode`
+fn parse_opcode(s: &str) -> Option<OpCode>
+{
+    match s.to_lowercase().as_str() {
+        "dat"         => Some(OpCode::Dat),
+        "mov"         => Some(OpCode::Mov),
+        "add"         => Some(OpCode::Add),
+        "sub"         => Some(OpCode::Sub),
+        "mul"         => Some(OpCode::Mul),
+        "div"         => Some(OpCode::Div),
+        "mod"         => Some(OpCode::Mod),
+        "jmp"         => Some(OpCode::Jmp),
+        "jmz"         => Some(OpCode::Jmz),
+        "jmn"         => Some(OpCode::Jmn),
+        "djn"         => Some(OpCode::Djn),
+        "spl"         => Some(OpCode::Spl),
+        "seq" | "cmp" => Some(OpCode::Seq),
+        "sne"         => Some(OpCode::Sne),
+        "slt"         => Some(OpCode::Slt),
+        "ldp"         => Some(OpCode::Ldp),
+        "stp"         => Some(OpCode::Stp),
+        "nop"         => Some(OpCode::Nop),
+        _             => None,
+    }
+}
+
+/// Convert the text of a modifier into a `Modifier`
+fn parse_modifier(s: &str) -> Option<Modifier>
+{
+    match s.to_lowercase().as_str() {
+        "a"  => Some(Modifier::A),
+        "b"  => Some(Modifier::B),
+        "ab" => Some(Modifier::AB),
+        "ba" => Some(Modifier::BA),
+        "x"  => Some(Modifier::X),
+        "f"  => Some(Modifier::F),
+        "i"  => Some(Modifier::I),
+        _    => None,
+    }
+}
+
+/// Convert the text of an addressing mode into an `AddressingMode`
+fn parse_mode(s: &str) -> Option<AddressingMode>
+{
+    match s {
+        "#" => Some(AddressingMode::Immediate),
+        "$" => Some(AddressingMode::Direct),
+        "*" => Some(AddressingMode::AIndirect),
+        "@" => Some(AddressingMode::BIndirect),
+        "{" => Some(AddressingMode::AIndirectPreDecrement),
+        "<" => Some(AddressingMode::BIndirectPreDecrement),
+        "}" => Some(AddressingMode::AIndirectPostIncrement),
+        ">" => Some(AddressingMode::BIndirectPostIncrement),
+        _   => None,
+    }
+}
+
+/// Parse an operand into its addressing mode and value
+///
+/// # Arguments
+/// * `operand`: token preceding the operand and the tokens of the operand
+/// * `lookup`: resolves a symbol to its value
+/// * `env`: environment whose core size the value is reduced by
+fn parse_operand<'a, F>(
+    operand: (&Token<'a>, &[Token<'a>]),
+    lookup:  &F,
+    env:     &Environment,
+    ) -> ParseResult<(AddressingMode, Value)>
+    where F: Fn(&Token<'a>) -> ParseResult<i64>
+{
+    let (mut prev, tokens) = operand;
+
+    let (mode, expr) = match tokens.first().and_then(|t| parse_mode(t.content)) {
+        Some(mode) => {
+            prev = &tokens[0];
+            (mode, &tokens[1..])
+        }
+        None       => (AddressingMode::Direct, tokens),
+    };
+
+    if expr.is_empty() {
+        return Err(ParseError::after(ParseErrorKind::MissingOperand, prev));
+    }
+
+    // values too large for a field are still correct modulo the core size
+    let mut value = evaluate(expr, lookup)?;
+    if value < Value::MIN as i64 || value > Value::MAX as i64 {
+        value = env.fold(value);
+    }
+
+    if value < Value::MIN as i64 || value > Value::MAX as i64 {
+        return Err(ParseError::new(
+            ParseErrorKind::ValueOutOfRange,
+            expr[0].start,
+            expr[expr.len() - 1].end
+            ));
+    }
+
+    Ok((mode, value as Value))
+}
+
+/// Parse a string into `Instruction`s placing them in a buffer
+///
+/// # Arguments
+/// * `program_str`: text of program
+/// * `buf`: buffer to place parsed data in
+///
+/// # Return
+/// `Ok(())` on success, otherwise the first error found
+pub fn parse_into<T>(program_str: &str, buf: &mut Vec<T>)
+    -> ParseResult<()>
+    where T: Instruction
+{
+    let mut warrior = Warrior::default();
+    let env = Environment::default();
+    let diagnostics = diagnose(program_str, &env, &mut warrior);
+    buf.append(&mut warrior.into_instructions());
+
+    match diagnostics.into_iter().find(|e| e.severity() == Severity::Error) {
+        Some(err) => Err(err),
+        None      => Ok(()),
+    }
+}
+
+/// Parse a string into `Instruction`s
+/// # Arguments
+/// `program_str`: text of program
+///
+/// # Return
+/// Vector contained `Instruction`s `program_str` was parsed into
+pub fn parse<T>(program_str: &str)
+    -> ParseResult<Vec<T>>
+    where T: Instruction
+{
+    let mut v = vec![];
+    parse_into(program_str, &mut v)?;
+    Ok(v)
+}
+
+/// Parse a string into `Instruction`s placing them in a buffer, continuing
+/// past errors to report every problem in the program
+///
+/// Instructions that cannot be read or assembled are replaced by
+/// `T::default()`, keeping the labels of their lines, so the buffer holds a
+/// best effort program when errors are returned
+///
+/// # Arguments
+/// * `program_str`: text of program
+/// * `buf`: buffer to place parsed data in
+///
+/// # Return
+/// every error and warning found, in the order they appear in the text
+pub fn parse_all_into<T>(program_str: &str, buf: &mut Vec<T>)
+    -> Vec<ParseError>
+    where T: Instruction
+{
+    let mut warrior = Warrior::default();
+    let env = Environment::default();
+    let mut diagnostics = diagnose(program_str, &env, &mut warrior);
+    buf.append(&mut warrior.into_instructions());
+
+    diagnostics.sort_by_key(|e| e.span());
+    diagnostics
+}
+
+/// Parse a string into `Instruction`s, continuing past errors to report every
+/// problem in the program
+///
+/// # Arguments
+/// * `program_str`: text of program
+///
+/// # Return
+/// best effort program and every error and warning found
+pub fn parse_all<T>(program_str: &str)
+    -> (Vec<T>, Vec<ParseError>)
+    where T: Instruction
+{
+    let mut v = vec![];
+    let diagnostics = parse_all_into(program_str, &mut v);
+    (v, diagnostics)
+}
+
+/// Parse a string into a `Warrior`, keeping the start of the program given by
+/// `ORG`, `END` or the label `start` and the metadata in its comments
+///
+/// # Arguments
+/// * `program_str`: text of program
+///
+/// # Return
+/// `Warrior` the string was parsed into, otherwise the first error found
+pub fn parse_warrior<T>(program_str: &str) -> ParseResult<Warrior<T>>
+    where T: Instruction
+{
+    parse_warrior_with(program_str, &Environment::default())
+}
+
+/// Parse a string into a `Warrior` for a particular `Mars`, whose settings
+/// are available to the program as predefined constants. Fails if an
+/// `;assert` comment does not hold in that environment
+///
+/// # Arguments
+/// * `program_str`: text of program
+/// * `env`: settings of the `Mars` the warrior will be loaded into
+///
+/// # Return
+/// `Warrior` the string was parsed into, otherwise the first error found
+pub fn parse_warrior_with<T>(program_str: &str, env: &Environment)
+    -> ParseResult<Warrior<T>>
+    where T: Instruction
+{
+    let mut warrior = Warrior::default();
+    match diagnose(program_str, env, &mut warrior)
+        .into_iter()
+        .find(|e| e.severity() == Severity::Error)
+    {
+        Some(err) => Err(err),
+        None      => Ok(warrior),
+    }
+}
+
+/// Run every stage of the parser over a string
+///
+/// # Arguments
+/// * `program_str`: text of program
+/// * `env`: settings of the `Mars` the program is assembled for
+/// * `warrior`: warrior to place parsed data in
+///
+/// # Return
+/// every error and warning found in the order they were discovered
+fn diagnose<T>(program_str: &str, env: &Environment, warrior: &mut Warrior<T>)
+    -> Vec<ParseError>
+    where T: Instruction
+{
+    let mut errors = vec![];
+    let unrolled = unroll(program_str, env, &mut errors);
+
+    let mut tokens = vec![];
+    let mut parse_errors = vec![];
+    lex_into(unrolled.text(), &mut tokens, &mut parse_errors);
+
+    Parser::new(unrolled.text(), &tokens, warrior, &mut parse_errors, env)
+        .run();
+
+    // spans in the unrolled text are moved back to the lines they came from,
+    // skipping stray `FOR`s and `ROF`s the unrolling already reported
+    let unrolling_errors = errors.len();
+    for e in parse_errors {
+        let e = e.map_span(|offset| {
+            unrolled.original_offset(offset, program_str)
+        });
+        if !errors[..unrolling_errors].contains(&e) {
+            errors.push(e);
+        }
+    }
+
+    errors.into_iter().map(|e| e.locate(program_str)).collect()
+}
+#[cfg(test)]
+mod test
+{
+    use super::*;
+    use redcode::Instruction as InstructionStruct;
+    use redcode::test_utils::instr;
+
+    #[test]
+    fn test_parse_dwarf_with_labels()
+    {
+        use redcode::types::AddressingMode::*;
+
+        let src = "
+            ;redcode-94
+            ;name Dwarf
+            step  EQU 4
+            start ADD.AB #step, bomb
+                  MOV.I  bomb, @bomb
+                  JMP.B  start
+            bomb  DAT.F  #0, #0
+            ";
+
+        assert_eq!(
+            Ok(vec![
+                instr(OpCode::Add, Modifier::AB, Immediate, 4, Direct, 3),
+                instr(OpCode::Mov, Modifier::I, Direct, 2, BIndirect, 2),
+                instr(OpCode::Jmp, Modifier::B, Direct, -2, Direct, 0),
+                instr(OpCode::Dat, Modifier::F, Immediate, 0, Immediate, 0),
+            ]),
+            parse::<InstructionStruct>(src)
+            );
+    }
+
+    #[test]
+    fn test_parse_expressions()
+    {
+        use redcode::types::AddressingMode::*;
+
+        let src = "
+            a    EQU (2 + 3) * 4
+            b    EQU a / 3 % 4 - (a > 10)
+            top  MOV.AB #a, {b
+                 DAT.F  last - top + 1, <-1
+            last
+            ";
+
+        assert_eq!(
+            Ok(vec![
+                instr(OpCode::Mov, Modifier::AB, Immediate, 20,
+                    AIndirectPreDecrement, 1),
+                instr(OpCode::Dat, Modifier::F, Direct, 3,
+                    BIndirectPreDecrement, -1),
+            ]),
+            parse::<InstructionStruct>(src)
+            );
+    }
+
+    #[test]
+    fn test_parse_multi_line_equate()
+    {
+        use redcode::types::AddressingMode::*;
+
+        let src = "
+            pair EQU MOV.I 0, 1
+                 EQU DAT.F #1, #2
+            pair
+            next JMP.B next
+            ";
+
+        assert_eq!(
+            Ok(vec![
+                instr(OpCode::Mov, Modifier::I, Direct, 0, Direct, 1),
+                instr(OpCode::Dat, Modifier::F, Immediate, 1, Immediate, 2),
+                instr(OpCode::Jmp, Modifier::B, Direct, 0, Direct, 0),
+            ]),
+            parse::<InstructionStruct>(src)
+            );
+    }
+
+    #[test]
+    fn test_parse_infers_default_modifiers()
+    {
+        use redcode::types::AddressingMode::*;
+
+        let src = "
+            imp  MOV 0, 1
+                 ADD #4, 3
+                 SPL #0
+                 SLT 1, #2
+                 DAT 5
+            ";
+
+        assert_eq!(
+            Ok(vec![
+                instr(OpCode::Mov, Modifier::I, Direct, 0, Direct, 1),
+                instr(OpCode::Add, Modifier::AB, Immediate, 4, Direct, 3),
+                instr(OpCode::Spl, Modifier::B, Immediate, 0, Direct, 0),
+                instr(OpCode::Slt, Modifier::B, Direct, 1, Immediate, 2),
+                instr(OpCode::Dat, Modifier::F, Immediate, 0, Direct, 5),
+            ]),
+            parse::<InstructionStruct>(src)
+            );
+    }
+
+    #[test]
+    fn test_parse_default_modifiers_follow_standard()
+    {
+        use redcode::Standard;
+
+        let mut env = Environment::new();
+        env.standard(Standard::Icws86);
+
+        let prog = parse_warrior_with::<InstructionStruct>("ADD 1, 2", &env)
+            .unwrap();
+        assert_eq!(Modifier::B, prog.instructions()[0].modifier());
+
+        env.standard(Standard::Icws88);
+        let prog = parse_warrior_with::<InstructionStruct>("ADD 1, 2", &env)
+            .unwrap();
+        assert_eq!(Modifier::F, prog.instructions()[0].modifier());
+    }
+
+    #[test]
+    fn test_parse_stops_at_end()
+    {
+        let prog = parse::<InstructionStruct>("DAT.F 0\nEND\nbogus text")
+            .unwrap();
+        assert_eq!(1, prog.len());
+    }
+
+    #[test]
+    fn test_parse_warrior_start()
+    {
+        let start = |src| parse_warrior::<InstructionStruct>(src)
+            .unwrap()
+            .start();
+
+        assert_eq!(0, start("DAT.F 0\nDAT.F 0"));
+        assert_eq!(1, start("ORG go\nDAT.F 0\ngo DAT.F 0"));
+        assert_eq!(2, start("DAT.F 0\nDAT.F 0\nDAT.F 0\nEND 1 + 1"));
+        assert_eq!(1, start("DAT.F 0\nstart DAT.F 0"));
+        assert_eq!(0, start("ORG 0\nDAT.F 0\nstart DAT.F 0"));
+
+        let (prog, diagnostics) = parse_all::<InstructionStruct>(
+            "ORG 0\nDAT.F 0\nDAT.F 0\nEND 1"
+            );
+        assert_eq!(2, prog.len());
+        assert_eq!(
+            vec![ParseErrorKind::StartRedefined],
+            diagnostics.iter().map(|e| e.kind().clone()).collect::<Vec<_>>()
+            );
+        assert_eq!(1, start("ORG 0\nDAT.F 0\nDAT.F 0\nEND 1"));
+    }
+
+    #[test]
+    fn test_parse_warrior_metadata()
+    {
+        let src = "
+            ; ignored, it precedes the ;redcode line
+            ;name ignored
+            ;redcode-94
+            ;name   Imp
+            ;author A. K. Dewdney
+            ;version 2
+            ;strategy  moves through the core
+            ;strategy  one step at a time
+            ;assert CORESIZE == 8000 && MAXPROCESSES >= 1
+            PIN 7 * 2
+            MOV.I 0, 1
+            ";
+
+        let warrior = parse_warrior::<InstructionStruct>(src).unwrap();
+        assert_eq!(Some("Imp"), warrior.name());
+        assert_eq!(Some("A. K. Dewdney"), warrior.author());
+        assert_eq!(Some("2"), warrior.version());
+        assert_eq!(
+            &vec!["moves through the core".to_owned(),
+                "one step at a time".to_owned()],
+            warrior.strategy()
+            );
+        assert_eq!(Some(14), warrior.pin());
+        assert_eq!(1, warrior.len());
+    }
+
+    #[test]
+    fn test_parse_warrior_assert()
+    {
+        let src = "size EQU CORESIZE\n;assert size == 800\nDAT.F 0";
+
+        let mut env = Environment::new();
+        env.core_size(800);
+        assert!(parse_warrior_with::<InstructionStruct>(src, &env).is_ok());
+
+        env.core_size(8000);
+        let err = parse_warrior_with::<InstructionStruct>(src, &env)
+            .unwrap_err();
+        assert_eq!(&ParseErrorKind::AssertionFailed, err.kind());
+        assert_eq!((2, 9), (err.line(), err.column()));
+    }
+
+    #[test]
+    fn test_parse_predefined_constants()
+    {
+        let src = "
+            step EQU CORESIZE / WARRIORS
+            MOV.I CURLINE, step
+            DAT.F MAXLENGTH + MINDISTANCE, ROUNDS * VERSION
+            DAT.F CURLINE, CORESIZE - 1
+            ";
+
+        let mut env = Environment::new();
+        env.core_size(55440).rounds(3);
+
+        let prog = parse_warrior_with::<InstructionStruct>(src, &env)
+            .unwrap()
+            .into_instructions();
+
+        let values: Vec<_> = prog.iter().map(|i| (i.a(), i.b())).collect();
+        assert_eq!(vec![(0, 27720), (200, 240), (2, 55439)], values);
+    }
+
+    #[test]
+    fn test_parse_range_limits()
+    {
+        let src = "DAT.F READLIMIT, WRITELIMIT";
+
+        let mut env = Environment::new();
+        env.core_size(800);
+        let prog = parse_warrior_with::<InstructionStruct>(src, &env)
+            .unwrap();
+        let dat = &prog.instructions()[0];
+        assert_eq!((800, 800), (dat.a(), dat.b()));
+
+        env.read_limit(300).write_limit(100);
+        let prog = parse_warrior_with::<InstructionStruct>(src, &env)
+            .unwrap();
+        let dat = &prog.instructions()[0];
+        assert_eq!((300, 100), (dat.a(), dat.b()));
+    }
+
+    #[test]
+    fn test_parse_for_blocks()
+    {
+        use redcode::types::AddressingMode::*;
+
+        let src = "
+              i FOR 2
+            ptr&i DAT.F #i, ptr01
+                  ROF
+                  JMP.B ptr02
+            ";
+
+        assert_eq!(
+            Ok(vec![
+                instr(OpCode::Dat, Modifier::F, Immediate, 1, Direct, 0),
+                instr(OpCode::Dat, Modifier::F, Immediate, 2, Direct, -1),
+                instr(OpCode::Jmp, Modifier::B, Direct, -1, Direct, 0),
+            ]),
+            parse::<InstructionStruct>(src)
+            );
+
+        // errors inside a block point at the line they were written on
+        let src = "FOR 3\nDAT.F 0\nDAT.F x\nROF";
+        let (_, diagnostics) = parse_all::<InstructionStruct>(src);
+        assert_eq!(3, diagnostics.len());
+        assert!(diagnostics.iter().all(|e| (e.line(), e.column()) == (3, 7)));
+    }
+
+    #[test]
+    fn test_load_file_round_trip()
+    {
+        let src = "
+            ;redcode-94
+            ;name Dwarf
+            ;author A. K. Dewdney
+            ;strategy bombs every fourth instruction
+            ;assert CORESIZE % 4 == 0
+            step  EQU 4
+            bomb  DAT   #0
+            start ADD   #step, bomb
+                  MOV   bomb, @bomb
+                  JMP   start
+                  NOP   }1, <2
+                  END   start
+            ";
+
+        let warrior = parse_warrior::<InstructionStruct>(src).unwrap();
+        let load_file = warrior.to_string();
+
+        // layout of the listing `pmars -r 0` prints
+        assert_eq!(
+            concat!(
+                ";redcode-94\n",
+                ";name Dwarf\n",
+                ";author A. K. Dewdney\n",
+                ";strategy bombs every fourth instruction\n",
+                ";assert CORESIZE % 4 == 0\n",
+                "       ORG      START\n",
+                "       DAT.F  #     0, #     0\n",
+                "START  ADD.AB #     4, $    -1\n",
+                "       MOV.I  $    -2, @    -2\n",
+                "       JMP.B  $    -2, $     0\n",
+                "       NOP.F  }     1, <     2\n",
+                "       END\n",
+                ),
+            load_file
+            );
+        assert_eq!(Ok(warrior), parse_warrior(&load_file));
+    }
+
+    #[test]
+    fn test_load_file_keeps_redcode_line()
+    {
+        let src = ";redcode-94x\n;name Imp\nPIN 3\nMOV 0, 1";
+        let warrior = parse_warrior::<InstructionStruct>(src).unwrap();
+
+        assert_eq!(
+            concat!(
+                ";redcode-94x\n",
+                ";name Imp\n",
+                "       PIN      3\n",
+                "       ORG      START\n",
+                "START  MOV.I  $     0, $     1\n",
+                "       END\n",
+                ),
+            warrior.to_string()
+            );
+        assert_eq!(Ok(warrior.clone()), parse_warrior(&warrior.to_string()));
+
+        // warriors without a `;redcode` line are written without one
+        let warrior = parse_warrior::<InstructionStruct>("SPL.AB <-1, 2")
+            .unwrap();
+        assert_eq!(
+            concat!(
+                "       ORG      START\n",
+                "START  SPL.AB <    -1, $     2\n",
+                "       END\n",
+                ),
+            warrior.to_string()
+            );
+    }
+
+    fn parse_err(program_str: &str) -> (ParseErrorKind, usize, usize)
+    {
+        let err = parse::<InstructionStruct>(program_str).unwrap_err();
+        (err.kind().clone(), err.line(), err.column())
+    }
+
+    #[test]
+    fn test_parse_errors()
+    {
+        use self::ParseErrorKind::*;
+
+        assert_eq!(
+            (UndefinedLabel("nowhere".to_owned()), 1, 7),
+            parse_err("JMP.B nowhere")
+            );
+        assert_eq!(
+            (DuplicateLabel("x".to_owned()), 2, 1),
+            parse_err("x MOV.I 0, 1\nx DAT.F 0")
+            );
+        assert_eq!(
+            (RecursiveEquate("a".to_owned()), 2, 7),
+            parse_err("a EQU b\nb EQU a\nDAT.F a")
+            );
+        assert_eq!((DivisionByZeroInExpression, 1, 8), parse_err("DAT.F 1/0"));
+        assert_eq!((UnknownModifier, 1, 5), parse_err("MOV.Q 0, 1"));
+        assert_eq!((UnknownOpcode, 2, 3), parse_err("\n  MVO.I 0, 1"));
+        assert_eq!((MissingOperand, 1, 11), parse_err("MOV.I 0, #"));
+        assert_eq!((TooManyOperands, 1, 11), parse_err("MOV.I 0, 1, 2"));
+        assert_eq!((MissingEquateName, 1, 1), parse_err("EQU 4"));
+        assert_eq!((MissingOperand, 1, 4), parse_err("ORG\nDAT.F 0"));
+        assert_eq!((ValueOutOfRange, 2, 5), parse_err("DAT.F 0\nEND 1"));
+    }
+
+    #[test]
+    fn test_parse_all_reports_every_error()
+    {
+        use self::ParseErrorKind::*;
+
+        let src = "
+            start MOV.I 0, 1 ?
+                  ADD.AB #1, nowhere
+                  MVO.I 0, 1
+                  JMP.B start
+                  DAT.F 1/0
+            END
+            DAT.F 0
+            ";
+
+        let (prog, diagnostics) = parse_all::<InstructionStruct>(src);
+
+        let kinds: Vec<_> = diagnostics.iter()
+            .map(|e| (e.kind().clone(), e.line(), e.severity()))
+            .collect();
+
+        assert_eq!(
+            vec![
+                (UnexpectedCharacter, 2, Severity::Error),
+                (UndefinedLabel("nowhere".to_owned()), 3, Severity::Error),
+                (UnknownOpcode, 4, Severity::Error),
+                (DivisionByZeroInExpression, 6, Severity::Error),
+                (TextAfterEnd, 8, Severity::Warning),
+            ],
+            kinds
+            );
+
+        // the failed instructions are kept as placeholders, along with the
+        // labels of their lines
+        assert_eq!(5, prog.len());
+        for &i in [0, 1, 2, 4].iter() {
+            assert_eq!(InstructionStruct::default(), prog[i]);
+        }
+        assert_eq!(
+            instr(OpCode::Jmp, Modifier::B, AddressingMode::Direct, -3,
+                AddressingMode::Direct, 0),
+            prog[3]
+            );
+    }
+
+    #[test]
+    fn test_parse_all_keeps_labels_of_failed_lines()
+    {
+        let src = "MOV.I 0, 1\nbad MVO.I 0, 1\nx EQU 1 ?\nJMP.B bad, x";
+        let (prog, diagnostics) = parse_all::<InstructionStruct>(src);
+
+        let kinds: Vec<_> = diagnostics.iter()
+            .map(|e| (e.kind().clone(), e.line()))
+            .collect();
+        assert_eq!(
+            vec![
+                (ParseErrorKind::UnknownOpcode, 2),
+                (ParseErrorKind::UnexpectedCharacter, 3),
+            ],
+            kinds
+            );
+
+        // the jump using the broken equate is left as a placeholder
+        assert_eq!(3, prog.len());
+        assert_eq!(InstructionStruct::default(), prog[2]);
+    }
+
+    #[test]
+    fn test_parse_all_stray_for_and_rof()
+    {
+        use self::ParseErrorKind::*;
+
+        for src in &["x EQU FOR 2\nDAT 0", "DAT 0 ROF\nDAT 0"] {
+            let (_, diagnostics) = parse_all::<InstructionStruct>(src);
+            let kinds: Vec<_> = diagnostics.iter()
+                .map(|e| (e.kind().clone(), e.line(), e.column()))
+                .collect();
+            assert_eq!(vec![(UnexpectedToken, 1, 7)], kinds);
+        }
+    }
+
+    #[test]
+    fn test_parse_all_locates_errors_in_unrolled_lines()
+    {
+        let locate = |src| {
+            let (_, diagnostics) = parse_all::<InstructionStruct>(src);
+            diagnostics.iter()
+                .map(|e| (e.kind().clone(), e.line(), e.column()))
+                .collect::<Vec<_>>()
+        };
+
+        // `&idx` is replaced by the shorter `01`
+        assert_eq!(
+            vec![(ParseErrorKind::UnexpectedCharacter, 2, 14)],
+            locate("idx FOR 1\nx&idx DAT 0, ?\nROF")
+            );
+
+        // `i` is replaced by the longer `10`, in front of non-ASCII text
+        assert_eq!(
+            vec![(ParseErrorKind::UnexpectedCharacter, 2, 8); 10],
+            locate("i FOR 10\nDAT i, \u{e9}\u{e9}\nROF")
+            );
+        assert_eq!(
+            vec![(ParseErrorKind::UnexpectedCharacter, 2, 11); 10],
+            locate("i FOR 10\nDAT i, i, \u{e9}x\nROF")
+            );
+
+        // offsets inside a character are moved to its start
+        let err = ParseError::new(ParseErrorKind::UnexpectedCharacter, 2, 4)
+            .locate("x\u{e9}\u{e9}");
+        assert_eq!((1, 1, 2), (err.span().0, err.line(), err.column()));
+    }
+
+    #[test]
+    fn test_parse_warnings_are_not_errors()
+    {
+        assert!(parse::<InstructionStruct>("DAT.F 0\nEND\nDAT.F 0").is_ok());
+    }
+
+    #[test]
+    fn test_parse_error_display()
+    {
+        let err = parse::<InstructionStruct>("DAT.F 0\n DAT.F x").unwrap_err();
+        assert_eq!("2:8: undefined label `x`", err.to_string());
+    }
+}
@@ -0,0 +1,699 @@
+//! Unrolling of `FOR`/`ROF` repetition blocks
+//!
+//! Blocks are unrolled in the text of the program, so that labels built with
+//! `&` can be lexed as ordinary names. Every piece of the unrolled text
+//! remembers the text of the original program it was made from
+
+use redcode::Instruction as InstructionStruct;
+use redcode::Warrior;
+
+use super::{
+    Parser,
+    Environment,
+    ParseError,
+    ParseErrorKind,
+    ParseResult,
+    is_ident,
+    is_pseudo_op
+};
+use super::lexer::{Token, TokenKind, lex_into};
+
+/// Text of a program with its repetition blocks unrolled
+pub(super) struct Unrolled
+{
+    /// Text with every block unrolled
+    text:  String,
+
+    /// Lines of `text`, in order
+    lines: Vec<Line>,
+}
+
+/// A line of unrolled text
+struct Line
+{
+    /// Offset of the line in the unrolled text
+    start:    usize,
+
+    /// Pieces the line is made of, never empty
+    segments: Vec<Segment>,
+}
+
+/// Part of a line of unrolled text, either copied from the original program
+/// or replacing a block counter written in it
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct Segment
+{
+    /// Offset of the segment in its line
+    start:  usize,
+
+    /// Offset of the text of the original program it was made from
+    origin: usize,
+
+    /// Length of the text of the original program it was made from. Longer
+    /// replacements map their extra bytes to its end
+    len:    usize,
+}
+
+/// A line of text along with its tokens
+struct SourceLine<'a>
+{
+    /// Text of the line
+    text:   &'a str,
+
+    /// Offset of the line in the text it was taken from
+    start:  usize,
+
+    /// Pieces the line is made of
+    segments: &'a [Segment],
+
+    /// Tokens of the line without comments
+    tokens: Vec<Token<'a>>,
+}
+
+/// A `FOR` block whose `ROF` has not been found yet
+struct Block<'a>
+{
+    /// The `FOR` token
+    start:   Token<'a>,
+
+    /// Name replaced by the iteration number inside the block
+    counter: Option<&'a str>,
+
+    /// Number of times the block is repeated
+    count:   usize,
+
+    /// Span of the expression giving the number of repetitions
+    span:    (usize, usize),
+
+    /// Indices of the lines of the block
+    body:    Vec<usize>,
+
+    /// Number of `FOR`s nested inside the block that are still open
+    depth:   usize,
+}
+
+impl Unrolled
+{
+    /// Create an `Unrolled` from text with no blocks unrolled yet
+    fn identity(program_str: &str) -> Self
+    {
+        let mut start = 0;
+        let lines = program_str.split('\n')
+            .map(|text| {
+                let segment = Segment {
+                    start:  0,
+                    origin: start,
+                    len:    text.len(),
+                };
+                let line = Line { start, segments: vec![segment] };
+                start += text.len() + 1;
+                line
+            })
+            .collect();
+
+        Unrolled { text: program_str.to_owned(), lines }
+    }
+
+    /// Create an `Unrolled` from lines of text and the pieces they are made
+    /// of
+    fn from_lines(lines: Vec<(String, Vec<Segment>)>) -> Self
+    {
+        let mut unrolled = Unrolled { text: String::new(), lines: vec![] };
+
+        for (i, (text, segments)) in lines.into_iter().enumerate() {
+            if i > 0 {
+                unrolled.text.push('\n');
+            }
+            let start = unrolled.text.len();
+            unrolled.lines.push(Line { start, segments });
+            unrolled.text.push_str(&text);
+        }
+
+        unrolled
+    }
+
+    /// Text with every block unrolled
+    pub(super) fn text(&self) -> &str
+    {
+        &self.text
+    }
+
+    /// Map a byte offset of the unrolled text to the original program.
+    /// Offsets in a replaced block counter map into the counter
+    ///
+    /// # Arguments
+    /// * `offset`: byte offset into the unrolled text
+    /// * `program_str`: text of the original program
+    pub(super) fn original_offset(&self, offset: usize, program_str: &str)
+        -> usize
+    {
+        let i = match self.lines.binary_search_by_key(&offset, |l| l.start) {
+            Ok(i)  => i,
+            Err(i) => i.saturating_sub(1),
+        };
+
+        let line = match self.lines.get(i) {
+            Some(line) => line,
+            None       => return offset.min(program_str.len()),
+        };
+
+        original(&line.segments, offset.saturating_sub(line.start))
+            .min(program_str.len())
+    }
+}
+
+/// Map a column of a line of unrolled text to the original program
+///
+/// # Arguments
+/// * `segments`: pieces the line is made of
+/// * `column`: byte offset into the line
+fn original(segments: &[Segment], column: usize) -> usize
+{
+    let segment = segments.iter()
+        .rev()
+        .find(|s| s.start <= column)
+        .unwrap_or(&segments[0]);
+
+    segment.origin + column.saturating_sub(segment.start).min(segment.len)
+}
+
+impl<'a> SourceLine<'a>
+{
+    /// The line unchanged, as a line of unrolled text
+    fn whole(&self) -> (String, Vec<Segment>)
+    {
+        (self.text.to_owned(), self.segments.to_vec())
+    }
+
+    /// Append part of the line to a line of unrolled text being made
+    ///
+    /// # Arguments
+    /// * `from`: offset in the line of the first byte to copy
+    /// * `to`: offset in the line following the last byte to copy
+    /// * `out`: line being made
+    fn copy_into(
+        &self,
+        from: usize,
+        to:   usize,
+        out:  &mut (String, Vec<Segment>),
+        )
+    {
+        for (k, segment) in self.segments.iter().enumerate() {
+            let end = self.segments.get(k + 1)
+                .map_or(self.text.len(), |next| next.start);
+            let (first, last) = (from.max(segment.start), to.min(end));
+
+            if first < last {
+                let origin = original(&self.segments[k..k + 1], first);
+                let origin_end = original(&self.segments[k..k + 1], last);
+                out.1.push(Segment {
+                    start:  out.0.len() + first - from,
+                    origin,
+                    len:    origin_end - origin,
+                });
+            }
+        }
+
+        out.0.push_str(&self.text[from..to]);
+    }
+
+    /// Append text replacing part of the line to a line of unrolled text
+    /// being made
+    ///
+    /// # Arguments
+    /// * `from`: offset in the line of the first byte replaced
+    /// * `to`: offset in the line following the last byte replaced
+    /// * `with`: replacement text
+    /// * `out`: line being made
+    fn replace_into(
+        &self,
+        from: usize,
+        to:   usize,
+        with: &str,
+        out:  &mut (String, Vec<Segment>),
+        )
+    {
+        let origin = original(self.segments, from);
+        let origin_end = original(self.segments, to);
+        out.1.push(Segment {
+            start:  out.0.len(),
+            origin,
+            len:    origin_end.saturating_sub(origin),
+        });
+        out.0.push_str(with);
+    }
+}
+
+/// Unroll every `FOR` block of a program, recording errors and continuing
+/// whenever one is found
+///
+/// # Arguments
+/// * `program_str`: text of program
+/// * `env`: settings of the `Mars` the program is assembled for
+/// * `errors`: buffer to place errors in, with spans in the original program
+///
+/// # Return
+/// text of the program with every block unrolled
+pub(super) fn unroll(
+    program_str: &str,
+    env:         &Environment,
+    errors:      &mut Vec<ParseError>,
+    ) -> Unrolled
+{
+    let mut unrolled = Unrolled::identity(program_str);
+    report_stray_keywords(&unrolled, errors);
+
+    // repetitions cannot add more lines than a warrior can hold
+    let max_length = env.constant("MAXLENGTH").unwrap_or(0) as usize;
+    let max_lines = unrolled.lines.len() + max_length;
+
+    // nested blocks are unrolled one level at a time
+    while let Some(next) = unroll_once(
+        &unrolled,
+        program_str,
+        env,
+        max_lines,
+        errors
+        )
+    {
+        unrolled = next;
+    }
+
+    unrolled
+}
+
+/// Report `FOR` and `ROF` used anywhere but in place of an opcode, where
+/// they would not open or close a block
+///
+/// # Arguments
+/// * `program`: program with no blocks unrolled yet
+/// * `errors`: buffer to place errors in
+fn report_stray_keywords(program: &Unrolled, errors: &mut Vec<ParseError>)
+{
+    let mut tokens = vec![];
+    lex_into(program.text(), &mut tokens, &mut vec![]);
+
+    for line in source_lines(program, &tokens) {
+        let k = keyword(&line);
+        if k.is_some_and(|k| is_pseudo_op(&line.tokens[k], "end")) {
+            break;
+        }
+
+        let stray = line.tokens.iter()
+            .enumerate()
+            .filter(|&(j, t)| {
+                Some(j) != k
+                    && (is_pseudo_op(t, "for") || is_pseudo_op(t, "rof"))
+            });
+
+        for (_, token) in stray {
+            errors.push(ParseError::at(ParseErrorKind::UnexpectedToken, token));
+        }
+    }
+}
+
+/// Position of the token in place of an opcode in a line, following its
+/// labels
+///
+/// # Arguments
+/// * `line`: line to search
+fn keyword(line: &SourceLine) -> Option<usize>
+{
+    line.tokens.iter().position(|t| !is_ident(t) && t.content != ":")
+}
+
+/// Unroll the outermost `FOR` blocks of a program
+///
+/// # Arguments
+/// * `current`: program unrolled so far
+/// * `program_str`: text of the original program
+/// * `env`: settings of the `Mars` the program is assembled for
+/// * `max_lines`: number of lines the unrolled program cannot go over
+/// * `errors`: buffer to place errors in, with spans in the original program
+///
+/// # Return
+/// the unrolled program, or `None` if there were no blocks
+fn unroll_once(
+    current:     &Unrolled,
+    program_str: &str,
+    env:         &Environment,
+    max_lines:   usize,
+    errors:      &mut Vec<ParseError>,
+    ) -> Option<Unrolled>
+{
+    // lexing errors are reported when the unrolled program is parsed
+    let mut tokens = vec![];
+    lex_into(current.text(), &mut tokens, &mut vec![]);
+    let lines = source_lines(current, &tokens);
+
+    // blocks following `END` are left alone
+    let has_blocks = lines.iter()
+        .filter_map(|line| keyword(line).map(|k| &line.tokens[k]))
+        .take_while(|t| !is_pseudo_op(t, "end"))
+        .any(|t| is_pseudo_op(t, "for") || is_pseudo_op(t, "rof"));
+
+    if !has_blocks {
+        return None;
+    }
+
+    // block counts may refer to equates
+    let mut scratch = Warrior::<InstructionStruct>::default();
+    let mut scratch_errors = vec![];
+    let mut parser = Parser::new(
+        current.text(),
+        &tokens,
+        &mut scratch,
+        &mut scratch_errors,
+        env
+        );
+    parser.collect_equates();
+
+    let mut output = vec![];
+    let mut found = vec![];
+    let mut block: Option<Block> = None;
+    let mut ended = false;
+
+    for (i, line) in lines.iter().enumerate() {
+        let keyword = keyword(line);
+        let is_keyword = |name| keyword.is_some_and(|k| {
+            is_pseudo_op(&line.tokens[k], name)
+        });
+
+        if let Some(ref mut b) = block {
+            if is_keyword("for") {
+                b.depth += 1;
+            } else if is_keyword("rof") {
+                b.depth -= 1;
+            }
+
+            if b.depth > 0 {
+                b.body.push(i);
+                continue;
+            }
+        }
+
+        if let Some(b) = block.take() {
+            let count = if output.len() + b.count * b.body.len() > max_lines {
+                found.push(ParseError::new(
+                    ParseErrorKind::ValueOutOfRange,
+                    b.span.0,
+                    b.span.1
+                    ));
+                0
+            } else {
+                b.count
+            };
+
+            for n in 1..count + 1 {
+                for &j in b.body.iter() {
+                    output.push(substitute(&lines[j], b.counter, n));
+                }
+            }
+        } else if ended {
+            output.push(line.whole());
+        } else if is_keyword("for") {
+            let k = keyword.unwrap();
+            let labels: Vec<&Token> = line.tokens[..k].iter()
+                .filter(|t| is_ident(t))
+                .collect();
+
+            // every label but the counter belongs to the next instruction
+            if labels.len() > 1 {
+                let mut text = (String::new(), vec![]);
+                for label in labels[..labels.len() - 1].iter() {
+                    if !text.0.is_empty() {
+                        text.0.push(' ');
+                    }
+                    line.copy_into(
+                        label.start - line.start,
+                        label.end - line.start,
+                        &mut text
+                        );
+                }
+                output.push(text);
+            }
+
+            let statement = &line.tokens[k..];
+            let count = match block_count(&parser, statement) {
+                Ok(count) => count,
+                Err(err)  => {
+                    found.push(err);
+                    0
+                }
+            };
+            let first = statement.get(1).unwrap_or(&statement[0]);
+            let last = statement[statement.len() - 1];
+
+            block = Some(Block {
+                start:   line.tokens[k],
+                counter: labels.last().map(|t| t.content),
+                count,
+                span:    (first.start, last.end),
+                body:    vec![],
+                depth:   1,
+            });
+        } else if is_keyword("rof") {
+            found.push(ParseError::at(
+                ParseErrorKind::UnmatchedRof,
+                &line.tokens[keyword.unwrap()]
+                ));
+        } else {
+            ended = is_keyword("end");
+            output.push(line.whole());
+        }
+    }
+
+    if let Some(b) = block {
+        found.push(ParseError::at(ParseErrorKind::UnmatchedFor, &b.start));
+    }
+
+    errors.extend(found.into_iter().map(|e| {
+        e.map_span(|offset| current.original_offset(offset, program_str))
+    }));
+
+    Some(Unrolled::from_lines(output))
+}
+
+/// Split a program into lines, pairing each with its tokens
+///
+/// # Arguments
+/// * `current`: program unrolled so far
+/// * `tokens`: tokens of the program
+fn source_lines<'a>(current: &'a Unrolled, tokens: &[Token<'a>])
+    -> Vec<SourceLine<'a>>
+{
+    let mut lines = vec![];
+    let mut rest = tokens.iter().peekable();
+
+    for (source, text) in current.lines.iter()
+        .zip(current.text().split('\n'))
+    {
+        let mut line = SourceLine {
+            text,
+            start:    source.start,
+            segments: &source.segments,
+            tokens:   vec![],
+        };
+        let end = source.start + text.len();
+
+        while let Some(token) = rest.next_if(|t| t.start <= end) {
+            if token.kind != TokenKind::Newline
+                && token.kind != TokenKind::Comment
+            {
+                line.tokens.push(*token);
+            }
+        }
+
+        lines.push(line);
+    }
+
+    lines
+}
+
+/// Evaluate the number of times a block is repeated
+///
+/// # Arguments
+/// * `parser`: parser holding the equates of the program
+/// * `statement`: the `FOR` token and the tokens following it
+fn block_count<'a, T>(parser: &Parser<'a, '_, T>, statement: &[Token<'a>])
+    -> ParseResult<usize>
+    where T: ::redcode::traits::Instruction
+{
+    let expr = &statement[1..];
+    if expr.is_empty() {
+        return Err(ParseError::after(
+            ParseErrorKind::MissingOperand,
+            &statement[0]
+            ));
+    }
+
+    let count = parser.evaluate_expanded(expr)?;
+    if count < 0 {
+        return Err(ParseError::new(
+            ParseErrorKind::ValueOutOfRange,
+            expr[0].start,
+            expr[expr.len() - 1].end
+            ));
+    }
+
+    Ok(count as usize)
+}
+
+/// Replace the counter of a block in a line with the iteration number.
+/// `name&counter` becomes `name` followed by the number in two digits
+///
+/// # Arguments
+/// * `line`: line to replace the counter in
+/// * `counter`: name of the counter
+/// * `n`: iteration number
+///
+/// # Return
+/// the line of unrolled text and the pieces it is made of
+fn substitute(line: &SourceLine, counter: Option<&str>, n: usize)
+    -> (String, Vec<Segment>)
+{
+    let counter = match counter {
+        Some(counter) => counter,
+        None          => return line.whole(),
+    };
+
+    let mut out = (String::new(), vec![]);
+    let mut copied = 0;
+
+    for (i, token) in line.tokens.iter().enumerate() {
+        if !is_ident(token) || token.content != counter {
+            continue;
+        }
+
+        let concatenated = i > 0 && line.tokens[i - 1].content == "&";
+        let from = if concatenated {
+            line.tokens[i - 1].start - line.start
+        } else {
+            token.start - line.start
+        };
+
+        let number = if concatenated {
+            format!("{:02}", n)
+        } else {
+            n.to_string()
+        };
+
+        line.copy_into(copied, from, &mut out);
+        line.replace_into(from, token.end - line.start, &number, &mut out);
+        copied = token.end - line.start;
+    }
+
+    line.copy_into(copied, line.text.len(), &mut out);
+    if out.1.is_empty() {
+        out.1.extend(line.segments.first().cloned());
+    }
+    out
+}
+
+#[cfg(test)]
+mod test
+{
+    use super::*;
+
+    fn unroll_text(program_str: &str) -> (String, Vec<ParseError>)
+    {
+        let mut errors = vec![];
+        let unrolled = unroll(program_str, &Environment::new(), &mut errors);
+        (unrolled.text().to_owned(), errors)
+    }
+
+    #[test]
+    fn test_unroll_counter_and_concatenation()
+    {
+        let (text, errors) = unroll_text("i FOR 2\nx&i DAT i\nROF\nEND");
+        assert!(errors.is_empty());
+        assert_eq!("x01 DAT 1\nx02 DAT 2\nEND", text);
+
+        let (text, errors) = unroll_text("END\nFOR 2\nROF");
+        assert!(errors.is_empty());
+        assert_eq!("END\nFOR 2\nROF", text);
+    }
+
+    #[test]
+    fn test_unroll_nested_blocks()
+    {
+        let src = "n EQU 2\ni FOR n\nj FOR i\nDAT i&j\nROF\nROF";
+        let (text, errors) = unroll_text(src);
+        assert!(errors.is_empty());
+        assert_eq!("n EQU 2\nDAT 101\nDAT 201\nDAT 202", text);
+    }
+
+    #[test]
+    fn test_unroll_maps_offsets_to_original_lines()
+    {
+        let src = "FOR 2\nDAT 0\nROF\nMOV 0, 1";
+        let mut errors = vec![];
+        let unrolled = unroll(src, &Environment::new(), &mut errors);
+
+        assert_eq!("DAT 0\nDAT 0\nMOV 0, 1", unrolled.text());
+        assert_eq!(10, unrolled.original_offset(10, src));
+        assert_eq!(16, unrolled.original_offset(12, src));
+    }
+
+    #[test]
+    fn test_unroll_maps_offsets_through_counters()
+    {
+        let src = "idx FOR 10\nx&idx DAT idx, 0\nROF";
+        let mut errors = vec![];
+        let unrolled = unroll(src, &Environment::new(), &mut errors);
+
+        let line = "x10 DAT 10, 0";
+        let start = unrolled.text().rfind(line).unwrap();
+        let column = |offset| {
+            unrolled.original_offset(start + offset, src) - "idx FOR 10\n".len()
+        };
+
+        // `x`, then `&idx` replaced by `10`, then `DAT`
+        assert_eq!(0, column(0));
+        assert_eq!(1, column(1));
+        assert_eq!(5, column(3));
+        assert_eq!(6, column(4));
+
+        // the counter `idx` replaced by `10`, then the rest of the line
+        assert_eq!(10, column(8));
+        assert_eq!(13, column(10));
+        assert_eq!(16, column(line.len()));
+    }
+
+    #[test]
+    fn test_unroll_unmatched()
+    {
+        let (_, errors) = unroll_text("ROF\nFOR 2\nDAT 0");
+        let kinds: Vec<_> = errors.iter().map(|e| e.kind().clone()).collect();
+        assert_eq!(
+            vec![ParseErrorKind::UnmatchedRof, ParseErrorKind::UnmatchedFor],
+            kinds
+            );
+        assert_eq!((4, 7), errors[1].span());
+    }
+
+    #[test]
+    fn test_unroll_stray_keywords()
+    {
+        let (text, errors) = unroll_text("x EQU FOR 2\nDAT 0 ROF\nDAT 0");
+        let kinds: Vec<_> = errors.iter().map(|e| e.kind().clone()).collect();
+        assert_eq!(vec![ParseErrorKind::UnexpectedToken; 2], kinds);
+        assert_eq!((6, 9), errors[0].span());
+        assert_eq!("x EQU FOR 2\nDAT 0 ROF\nDAT 0", text);
+    }
+
+    #[test]
+    fn test_unroll_limits_repetitions()
+    {
+        let (text, errors) = unroll_text("FOR 2000000000\nDAT 0\nROF");
+        assert_eq!(1, errors.len());
+        assert_eq!(&ParseErrorKind::ValueOutOfRange, errors[0].kind());
+        assert_eq!((4, 14), errors[0].span());
+        assert_eq!("", text);
+
+        // blocks can still fill a warrior
+        let (text, errors) = unroll_text("FOR 50\nDAT 0\nDAT 0\nROF");
+        assert!(errors.is_empty());
+        assert_eq!(100, text.lines().count());
+    }
+}
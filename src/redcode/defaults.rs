@@ -0,0 +1,26 @@
+//! Default settings of a `Mars`, shared by `MarsBuilder` and the parser's
+//! `Environment`
+
+/// Size of core's memory buffer
+pub(crate) const DEFAULT_SIZE: usize          = 8000;
+
+/// Size of each warrior's p-space
+pub(crate) const DEFAULT_PSPACE_SIZE: usize   = 500;
+
+/// Maximum number of cycles before game is considered a draw
+pub(crate) const DEFAULT_MAX_CYCLES: usize    = 80000;
+
+/// Maximum number of processes per warrior
+pub(crate) const DEFAULT_MAX_PROCESSES: usize = 8000;
+
+/// Maximum number of instructions a warrior can be comprised of
+pub(crate) const DEFAULT_MAX_LENGTH: usize    = 100;
+
+/// Minimum distance between two warriors
+pub(crate) const DEFAULT_MIN_DISTANCE: usize  = 100;
+
+/// Mars version multiplied by 100
+pub(crate) const DEFAULT_VERSION: usize       = 80;
+
+/// Number of warriors loaded in a battle
+pub(crate) const DEFAULT_WARRIORS: usize      = 2;
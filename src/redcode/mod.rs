@@ -4,7 +4,17 @@ pub mod traits;
 
 pub mod types;
 
+#[cfg(any(feature = "parser", feature = "mars"))]
+pub(crate) mod defaults;
+
 mod instruction;
 pub use self::instruction::Instruction;
 
+mod warrior;
+pub use self::warrior::Warrior;
+
+mod standard;
+pub use self::standard::Standard;
 
+#[cfg(all(test, any(feature = "parser", feature = "mars")))]
+pub(crate) mod test_utils;
@@ -0,0 +1,224 @@
+//! Redcode standards a `Mars` can follow
+
+use super::traits::Instruction;
+use super::types::{OpCode, Modifier, AddressingMode, default_modifier};
+
+/// Revision of the redcode language, deciding which instructions are legal
+/// and how some of them behave. Standards are ordered from oldest to newest
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Standard
+{
+    /// ICWS'86: no modifiers, `ADD`, `SUB` and `CMP` only use B fields and
+    /// `SPL` queues the new process before the next instruction
+    Icws86,
+
+    /// ICWS'88: no modifiers, only the `#`, `$`, `@` and `<` modes
+    Icws88,
+
+    /// ICWS'94 draft: modifiers, every addressing mode and the `MUL`, `DIV`,
+    /// `MOD`, `SNE` and `NOP` opcodes
+    Icws94Draft,
+
+    /// ICWS'94 draft along with the pMARS extensions `LDP` and `STP`
+    #[default]
+    PMars,
+}
+
+impl Standard
+{
+    /// Is an opcode part of the standard
+    ///
+    /// # Arguments
+    /// * `op`: opcode to check
+    pub fn supports_opcode(&self, op: OpCode) -> bool
+    {
+        match op {
+            OpCode::Dat
+                | OpCode::Mov
+                | OpCode::Add
+                | OpCode::Sub
+                | OpCode::Jmp
+                | OpCode::Jmz
+                | OpCode::Jmn
+                | OpCode::Djn
+                | OpCode::Spl
+                | OpCode::Seq => true,
+            OpCode::Slt => *self >= Standard::Icws88,
+            OpCode::Mul
+                | OpCode::Div
+                | OpCode::Mod
+                | OpCode::Sne
+                | OpCode::Nop => *self >= Standard::Icws94Draft,
+            OpCode::Ldp
+                | OpCode::Stp => *self == Standard::PMars,
+        }
+    }
+
+    /// Is an addressing mode part of the standard
+    ///
+    /// # Arguments
+    /// * `mode`: addressing mode to check
+    pub fn supports_mode(&self, mode: AddressingMode) -> bool
+    {
+        match mode {
+            AddressingMode::Immediate
+                | AddressingMode::Direct
+                | AddressingMode::BIndirect
+                | AddressingMode::BIndirectPreDecrement => true,
+            _ => *self >= Standard::Icws94Draft,
+        }
+    }
+
+    /// Can instructions be written with a modifier of their own. Otherwise
+    /// every instruction takes its default modifier
+    pub fn has_modifiers(&self) -> bool
+    {
+        *self >= Standard::Icws94Draft
+    }
+
+    /// Do instructions act on copies of their operands taken when the
+    /// operands are evaluated. Otherwise `SLT` and `DJN` read their operands
+    /// from memory as they execute
+    pub fn copies_operands(&self) -> bool
+    {
+        *self >= Standard::Icws94Draft
+    }
+
+    /// Does `SPL` queue the new process before the next instruction
+    pub fn splits_target_first(&self) -> bool
+    {
+        *self == Standard::Icws86
+    }
+
+    /// Modifier an instruction takes when it is written without one
+    ///
+    /// # Arguments
+    /// * `op`: opcode of the instruction
+    /// * `a_mode`: addressing mode of the A field
+    /// * `b_mode`: addressing mode of the B field
+    pub fn default_modifier(
+        &self,
+        op:     OpCode,
+        a_mode: AddressingMode,
+        b_mode: AddressingMode,
+        ) -> Modifier
+    {
+        let a_immediate = a_mode == AddressingMode::Immediate;
+
+        match op {
+            OpCode::Add
+                | OpCode::Sub
+                | OpCode::Seq if *self == Standard::Icws86 && !a_immediate =>
+                Modifier::B,
+            _ => default_modifier(op, a_mode, b_mode),
+        }
+    }
+
+    /// Is an instruction legal under the standard
+    ///
+    /// # Arguments
+    /// * `instr`: instruction to check
+    pub fn is_legal<T>(&self, instr: &T) -> bool
+        where T: Instruction
+    {
+        let (op, a_mode, b_mode) = (instr.op(), instr.a_mode(), instr.b_mode());
+
+        if !self.supports_opcode(op)
+            || !self.supports_mode(a_mode)
+            || !self.supports_mode(b_mode)
+        {
+            return false;
+        }
+
+        if self.has_modifiers() {
+            return true;
+        }
+
+        if instr.modifier() != self.default_modifier(op, a_mode, b_mode) {
+            return false;
+        }
+
+        // operand restrictions of the earlier standards
+        let immediate = AddressingMode::Immediate;
+        match op {
+            OpCode::Dat => [a_mode, b_mode].iter().all(|&m| {
+                m == immediate || m == AddressingMode::BIndirectPreDecrement
+            }),
+            OpCode::Mov
+                | OpCode::Add
+                | OpCode::Sub
+                | OpCode::Seq
+                | OpCode::Slt => b_mode != immediate,
+            OpCode::Jmp
+                | OpCode::Spl => a_mode != immediate,
+            OpCode::Jmz
+                | OpCode::Jmn
+                | OpCode::Djn => a_mode != immediate && b_mode != immediate,
+            _ => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test
+{
+    use super::*;
+    use redcode::Instruction as InstructionStruct;
+    use redcode::types::AddressingMode::*;
+
+    #[test]
+    fn test_supported_opcodes_and_modes()
+    {
+        assert!(!Standard::Icws86.supports_opcode(OpCode::Slt));
+        assert!(Standard::Icws88.supports_opcode(OpCode::Slt));
+        assert!(Standard::PMars.supports_opcode(OpCode::Slt));
+        assert!(!Standard::Icws88.supports_opcode(OpCode::Mul));
+        assert!(Standard::Icws94Draft.supports_opcode(OpCode::Nop));
+        assert!(!Standard::Icws94Draft.supports_opcode(OpCode::Ldp));
+        assert!(Standard::PMars.supports_opcode(OpCode::Stp));
+
+        assert!(Standard::Icws86.supports_mode(BIndirectPreDecrement));
+        assert!(!Standard::Icws88.supports_mode(AIndirect));
+        assert!(Standard::Icws94Draft.supports_mode(BIndirectPostIncrement));
+    }
+
+    #[test]
+    fn test_default_modifiers_by_standard()
+    {
+        assert_eq!(
+            Modifier::B,
+            Standard::Icws86.default_modifier(OpCode::Add, Direct, Direct)
+            );
+        assert_eq!(
+            Modifier::F,
+            Standard::Icws88.default_modifier(OpCode::Add, Direct, Direct)
+            );
+        assert_eq!(
+            Modifier::AB,
+            Standard::Icws86.default_modifier(OpCode::Sub, Immediate, Direct)
+            );
+    }
+
+    #[test]
+    fn test_is_legal()
+    {
+        let mov = InstructionStruct::new(
+            OpCode::Mov, Modifier::I, 0, Direct, 1, Direct);
+        assert!(Standard::Icws88.is_legal(&mov));
+
+        let mut with_modifier = mov;
+        with_modifier.set_modifier(Modifier::F);
+        assert!(!Standard::Icws88.is_legal(&with_modifier));
+        assert!(Standard::Icws94Draft.is_legal(&with_modifier));
+
+        let mut immediate_target = mov;
+        immediate_target.set_b_mode(Immediate);
+        immediate_target.set_modifier(Modifier::B);
+        assert!(!Standard::Icws88.is_legal(&immediate_target));
+
+        let dat = InstructionStruct::new(
+            OpCode::Dat, Modifier::F, 0, Direct, 0, Direct);
+        assert!(!Standard::Icws86.is_legal(&dat));
+        assert!(Standard::PMars.is_legal(&dat));
+    }
+}
@@ -0,0 +1,30 @@
+//! Helpers shared by the tests of every module
+
+use redcode::Instruction;
+#[cfg(feature = "mars")]
+use redcode::Warrior;
+use redcode::types::*;
+
+/// Build an instruction, listing each mode before its field like redcode
+/// does
+pub fn instr(
+    op:       OpCode,
+    modifier: Modifier,
+    a_mode:   AddressingMode,
+    a:        Value,
+    b_mode:   AddressingMode,
+    b:        Value,
+    ) -> Instruction
+{
+    Instruction::new(op, modifier, a, a_mode, b, b_mode)
+}
+
+/// A warrior that jumps to itself forever
+#[cfg(feature = "mars")]
+pub fn looper() -> Warrior<Instruction>
+{
+    Warrior::from(vec![
+        instr(OpCode::Jmp, Modifier::B, AddressingMode::Direct, 0,
+            AddressingMode::Direct, 0),
+    ])
+}
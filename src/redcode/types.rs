@@ -6,7 +6,7 @@ use std::fmt;
 pub type Address = u32;
 
 /// `Field` Value 
-pub type Value = i16;
+pub type Value = i32;
 
 /// Process ID
 pub type Pid = u16;
@@ -95,7 +95,7 @@ impl fmt::Display for OpCode
             OpCode::Slt => "slt",
             OpCode::Ldp => "ldp",
             OpCode::Stp => "stp",
-            OpCode::Nop => "stp",
+            OpCode::Nop => "nop",
         };
 
         write!(f, "{}", out_str)
@@ -216,3 +216,72 @@ impl fmt::Display for AddressingMode
     }
 }
 
+/// Modifier an instruction takes when it is written without one, following
+/// the defaults table of the ICWS'94 draft
+///
+/// # Arguments
+/// * `op`: opcode of the instruction
+/// * `a_mode`: addressing mode of the A field
+/// * `b_mode`: addressing mode of the B field
+pub fn default_modifier(
+    op:     OpCode,
+    a_mode: AddressingMode,
+    b_mode: AddressingMode,
+    ) -> Modifier
+{
+    let a_immediate = a_mode == AddressingMode::Immediate;
+    let b_immediate = b_mode == AddressingMode::Immediate;
+
+    match op {
+        OpCode::Dat
+            | OpCode::Nop => Modifier::F,
+        OpCode::Mov
+            | OpCode::Seq
+            | OpCode::Sne =>
+        {
+            if a_immediate      { Modifier::AB }
+            else if b_immediate { Modifier::B }
+            else                { Modifier::I }
+        }
+        OpCode::Add
+            | OpCode::Sub
+            | OpCode::Mul
+            | OpCode::Div
+            | OpCode::Mod =>
+        {
+            if a_immediate      { Modifier::AB }
+            else if b_immediate { Modifier::B }
+            else                { Modifier::F }
+        }
+        OpCode::Slt
+            | OpCode::Ldp
+            | OpCode::Stp => if a_immediate { Modifier::AB } else { Modifier::B },
+        OpCode::Jmp
+            | OpCode::Jmz
+            | OpCode::Jmn
+            | OpCode::Djn
+            | OpCode::Spl => Modifier::B,
+    }
+}
+
+#[cfg(test)]
+mod test
+{
+    use super::*;
+    use super::AddressingMode::*;
+
+    #[test]
+    fn test_default_modifier()
+    {
+        assert_eq!(Modifier::F,  default_modifier(OpCode::Dat, Immediate, Immediate));
+        assert_eq!(Modifier::I,  default_modifier(OpCode::Mov, Direct, Direct));
+        assert_eq!(Modifier::AB, default_modifier(OpCode::Mov, Immediate, Direct));
+        assert_eq!(Modifier::B,  default_modifier(OpCode::Seq, BIndirect, Immediate));
+        assert_eq!(Modifier::AB, default_modifier(OpCode::Add, Immediate, Direct));
+        assert_eq!(Modifier::F,  default_modifier(OpCode::Sub, Direct, AIndirect));
+        assert_eq!(Modifier::B,  default_modifier(OpCode::Slt, Direct, Immediate));
+        assert_eq!(Modifier::AB, default_modifier(OpCode::Stp, Immediate, Direct));
+        assert_eq!(Modifier::B,  default_modifier(OpCode::Jmp, Immediate, Direct));
+        assert_eq!(Modifier::B,  default_modifier(OpCode::Spl, Direct, Direct));
+    }
+}
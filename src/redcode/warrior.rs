@@ -0,0 +1,247 @@
+
+use std::fmt;
+
+use super::types::{Address, Pin};
+use super::traits;
+
+/// A redcode program along with the information needed to load it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Warrior<T>
+    where T: traits::Instruction
+{
+    instructions: Vec<T>,
+    start:        Address,
+    pin:          Option<Pin>,
+    name:         Option<String>,
+    author:       Option<String>,
+    version:      Option<String>,
+    strategy:     Vec<String>,
+    redcode:      Option<String>,
+    asserts:      Vec<String>,
+}
+
+impl<T> Warrior<T>
+    where T: traits::Instruction
+{
+    /// Create a warrior that starts executing at its first instruction
+    ///
+    /// # Arguments
+    /// * `instructions`: program of the warrior
+    pub fn new(instructions: Vec<T>) -> Self
+    {
+        Warrior {
+            instructions,
+            start:    0,
+            pin:      None,
+            name:     None,
+            author:   None,
+            version:  None,
+            strategy: vec![],
+            redcode:  None,
+            asserts:  vec![],
+        }
+    }
+
+    /// Get the instructions of the warrior
+    pub fn instructions(&self) -> &Vec<T>
+    {
+        &self.instructions
+    }
+
+    /// Get a mutable reference to the instructions of the warrior
+    pub fn instructions_mut(&mut self) -> &mut Vec<T>
+    {
+        &mut self.instructions
+    }
+
+    /// Consume the warrior, returning its instructions
+    pub fn into_instructions(self) -> Vec<T>
+    {
+        self.instructions
+    }
+
+    /// Number of instructions in the warrior
+    pub fn len(&self) -> usize
+    {
+        self.instructions.len()
+    }
+
+    /// Does the warrior have no instructions
+    pub fn is_empty(&self) -> bool
+    {
+        self.instructions.is_empty()
+    }
+
+    /// Offset of the first instruction executed, from the start of the
+    /// program
+    pub fn start(&self) -> Address
+    {
+        self.start
+    }
+
+    /// Set the offset of the first instruction executed
+    pub fn set_start(&mut self, v: Address)
+    {
+        self.start = v;
+    }
+
+    /// P-space identifier the warrior asks to be loaded with, warriors
+    /// sharing a PIN share p-space
+    pub fn pin(&self) -> Option<Pin>
+    {
+        self.pin
+    }
+
+    /// Set the P-space identifier of the warrior
+    pub fn set_pin(&mut self, v: Option<Pin>)
+    {
+        self.pin = v;
+    }
+
+    /// Name of the warrior
+    pub fn name(&self) -> Option<&str>
+    {
+        self.name.as_deref()
+    }
+
+    /// Set the name of the warrior
+    pub fn set_name(&mut self, v: Option<String>)
+    {
+        self.name = v;
+    }
+
+    /// Author of the warrior
+    pub fn author(&self) -> Option<&str>
+    {
+        self.author.as_deref()
+    }
+
+    /// Set the author of the warrior
+    pub fn set_author(&mut self, v: Option<String>)
+    {
+        self.author = v;
+    }
+
+    /// Version of the warrior
+    pub fn version(&self) -> Option<&str>
+    {
+        self.version.as_deref()
+    }
+
+    /// Set the version of the warrior
+    pub fn set_version(&mut self, v: Option<String>)
+    {
+        self.version = v;
+    }
+
+    /// Lines describing the strategy of the warrior
+    pub fn strategy(&self) -> &Vec<String>
+    {
+        &self.strategy
+    }
+
+    /// Get a mutable reference to the lines describing the strategy of the
+    /// warrior
+    pub fn strategy_mut(&mut self) -> &mut Vec<String>
+    {
+        &mut self.strategy
+    }
+
+    /// Text following `;redcode` on the warrior's `;redcode` line, e.g.
+    /// `-94`
+    pub fn redcode(&self) -> Option<&str>
+    {
+        self.redcode.as_deref()
+    }
+
+    /// Set the text following `;redcode` on the warrior's `;redcode` line
+    pub fn set_redcode(&mut self, v: Option<String>)
+    {
+        self.redcode = v;
+    }
+
+    /// Expressions of the warrior's `;assert` lines
+    pub fn asserts(&self) -> &Vec<String>
+    {
+        &self.asserts
+    }
+
+    /// Get a mutable reference to the expressions of the warrior's `;assert`
+    /// lines
+    pub fn asserts_mut(&mut self) -> &mut Vec<String>
+    {
+        &mut self.asserts
+    }
+}
+
+impl<T> Default for Warrior<T>
+    where T: traits::Instruction
+{
+    fn default() -> Self
+    {
+        Self::new(vec![])
+    }
+}
+
+impl<T> From<Vec<T>> for Warrior<T>
+    where T: traits::Instruction
+{
+    fn from(instructions: Vec<T>) -> Self
+    {
+        Self::new(instructions)
+    }
+}
+
+/// Writes the warrior in the layout of a pMARS load file: its metadata
+/// comments followed by the padded listing pMARS prints, e.g.
+///
+/// ```text
+///        ORG      START
+/// START  MOV.I  $     0, $     1
+///        END
+/// ```
+impl<T> fmt::Display for Warrior<T>
+    where T: traits::Instruction
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        if let Some(ref redcode) = self.redcode {
+            writeln!(f, ";redcode{}", redcode)?;
+        }
+        if let Some(ref name) = self.name {
+            writeln!(f, ";name {}", name)?;
+        }
+        if let Some(ref author) = self.author {
+            writeln!(f, ";author {}", author)?;
+        }
+        if let Some(ref version) = self.version {
+            writeln!(f, ";version {}", version)?;
+        }
+        for line in self.strategy.iter() {
+            writeln!(f, ";strategy {}", line)?;
+        }
+        for expr in self.asserts.iter() {
+            writeln!(f, ";assert {}", expr)?;
+        }
+        if let Some(pin) = self.pin {
+            writeln!(f, "       PIN      {}", pin)?;
+        }
+
+        writeln!(f, "       ORG      START")?;
+        for (i, instr) in self.instructions.iter().enumerate() {
+            let label = if i == self.start as usize { "START" } else { "" };
+            writeln!(
+                f,
+                "{:<6} {:>3}{:<3} {}{:>6}, {}{:>6}",
+                label,
+                instr.op().to_string().to_uppercase(),
+                format!(".{}", instr.modifier()),
+                instr.a_mode(),
+                instr.a(),
+                instr.b_mode(),
+                instr.b()
+                )?;
+        }
+        writeln!(f, "       END")
+    }
+}
@@ -0,0 +1,389 @@
+//! Running several rounds of the same warriors
+
+use redcode::types::*;
+use redcode::traits::Instruction;
+use redcode::Warrior;
+
+use simulation::{Mars, MarsBuilder, MatchOutcome, LoadResult, Placement};
+
+/// Result of a round for a single warrior
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RoundResult
+{
+    /// The warrior was the only one left running
+    Win,
+
+    /// The warrior had no processes left
+    Loss,
+
+    /// The warrior was still running when the round ended along with others
+    Tie,
+}
+
+/// Results of every round of a `Match`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatchResults
+{
+    /// Result of each warrior, in the order they were given, for each round
+    rounds: Vec<Vec<RoundResult>>,
+}
+
+impl MatchResults
+{
+    /// Result of each warrior, in the order they were given, for each round
+    pub fn rounds(&self) -> &Vec<Vec<RoundResult>>
+    {
+        &self.rounds
+    }
+
+    /// Number of rounds a warrior won
+    ///
+    /// # Arguments
+    /// * `warrior`: index of the warrior
+    pub fn wins(&self, warrior: usize) -> usize
+    {
+        self.count(warrior, RoundResult::Win)
+    }
+
+    /// Number of rounds a warrior lost
+    ///
+    /// # Arguments
+    /// * `warrior`: index of the warrior
+    pub fn losses(&self, warrior: usize) -> usize
+    {
+        self.count(warrior, RoundResult::Loss)
+    }
+
+    /// Number of rounds a warrior tied
+    ///
+    /// # Arguments
+    /// * `warrior`: index of the warrior
+    pub fn ties(&self, warrior: usize) -> usize
+    {
+        self.count(warrior, RoundResult::Tie)
+    }
+
+    /// Number of rounds a warrior had a result in
+    fn count(&self, warrior: usize, result: RoundResult) -> usize
+    {
+        self.rounds.iter().filter(|r| r[warrior] == result).count()
+    }
+}
+
+/// Runs several rounds of the same warriors on a `Mars`, keeping their
+/// p-space from one round to the next
+#[derive(Debug, Clone)]
+pub struct Match<'a, T>
+    where T: Instruction + 'a
+{
+    /// Configuration of the `Mars` the rounds are run on
+    builder:  MarsBuilder,
+
+    /// Warriors taking part, in load order
+    warriors: Vec<&'a Warrior<T>>,
+
+    /// Number of rounds to run
+    rounds:   usize,
+
+    /// Placement of the warriors, swept through every legal position if
+    /// `None`
+    placement: Option<Placement>,
+}
+
+impl<'a, T> Match<'a, T>
+    where T: Instruction
+{
+    /// Create a `Match` of a single round
+    ///
+    /// # Arguments
+    /// * `builder`: configuration of the `Mars` the rounds are run on
+    /// * `warriors`: warriors taking part, in load order
+    pub fn new(builder: &MarsBuilder, warriors: Vec<&'a Warrior<T>>) -> Self
+    {
+        Match {
+            builder: builder.clone(),
+            warriors,
+            rounds: 1,
+            placement: None,
+        }
+    }
+
+    /// Number of rounds to run
+    ///
+    /// # Arguments
+    /// * `n`: number of rounds
+    ///
+    /// # Return
+    /// `Self`
+    pub fn rounds(&mut self, n: usize) -> &mut Self
+    {
+        self.rounds = n;
+        self
+    }
+
+    /// Placement of the warriors. Random placements give new positions each
+    /// round, from the same seed every time the match is run
+    ///
+    /// # Arguments
+    /// * `placement`: placement of the warriors
+    ///
+    /// # Return
+    /// `Self`
+    pub fn placement(&mut self, placement: Placement) -> &mut Self
+    {
+        self.placement = Some(placement);
+        self
+    }
+
+    /// Run every round of the match
+    ///
+    /// Before each round after the first, location 0 of every warrior's
+    /// p-space is set to the result of its previous round: `0` for a loss,
+    /// otherwise the number of warriors still running when it ended
+    ///
+    /// # Return
+    /// results of every round on success, otherwise the error that prevented
+    /// the warriors from being loaded
+    pub fn run(&self) -> LoadResult<MatchResults>
+    {
+        let mut mars: Mars<T> = self.builder.build();
+        let mut results = MatchResults { rounds: vec![] };
+        let mut placement = self.placement;
+
+        for round in 0..self.rounds {
+            mars.reset();
+
+            let positions = match placement {
+                Some(ref mut placement) => placement.positions(
+                    self.warriors.len(),
+                    mars.size(),
+                    mars.min_distance()
+                    )?,
+                None => self.positions(&mars, round)?,
+            };
+            let programs = positions.iter()
+                .zip(self.warriors.iter())
+                .map(|(&dest, &warrior)| (dest, None, warrior))
+                .collect();
+            mars.load_batch(programs)?;
+
+            if let Some(last) = results.rounds.last() {
+                let survivors = last.iter()
+                    .filter(|&&r| r != RoundResult::Loss)
+                    .count();
+
+                for (pid, &result) in last.iter().enumerate() {
+                    let value = match result {
+                        RoundResult::Loss => 0,
+                        _                 => survivors as Value,
+                    };
+
+                    if let Some(key) = mars.pspace_key(pid as Pid) {
+                        mars.set_last_result(key, value);
+                    }
+                }
+            }
+
+            results.rounds.push(self.run_round(&mut mars));
+        }
+
+        Ok(results)
+    }
+
+    /// Run a loaded `Mars` until it halts
+    ///
+    /// # Return
+    /// result of each warrior
+    fn run_round(&self, mars: &mut Mars<T>) -> Vec<RoundResult>
+    {
+        while !mars.halted() {
+            let _ = mars.step();
+        }
+
+        let outcome = mars.outcome();
+
+        (0..self.warriors.len())
+            .map(|pid| {
+                let pid = pid as Pid;
+                let alive = mars.death_cycle(pid).is_none();
+
+                match outcome {
+                    Some(MatchOutcome::Win(winner)) if winner == pid =>
+                        RoundResult::Win,
+                    Some(MatchOutcome::Tie) if alive => RoundResult::Tie,
+                    _ => RoundResult::Loss,
+                }
+            })
+            .collect()
+    }
+
+    /// Load position of each warrior for a round when no placement is set.
+    /// The first warrior is always loaded at `0` and the others follow it
+    /// `min_distance` apart, shifted a little further each round until every
+    /// legal position has been used
+    ///
+    /// # Arguments
+    /// * `mars`: `Mars` the warriors will be loaded into
+    /// * `round`: number of the round, starting at `0`
+    fn positions(&self, mars: &Mars<T>, round: usize)
+        -> LoadResult<Vec<Address>>
+    {
+        let (size, min_distance) = (mars.size(), mars.min_distance());
+        let n = self.warriors.len();
+
+        let shift = round % (size.saturating_sub(n * min_distance) + 1);
+        Placement::Fixed((min_distance + shift) as Address)
+            .positions(n, size, min_distance)
+    }
+}
+
+#[cfg(test)]
+mod test
+{
+    use super::*;
+    use simulation::LoadError;
+    use redcode::Instruction as InstructionStruct;
+    use redcode::test_utils::{instr, looper};
+
+    #[test]
+    fn test_match_scores_each_round()
+    {
+        let dat = Warrior::from(vec![InstructionStruct::default()]);
+        let looper = looper();
+
+        let results = Match::new(&MarsBuilder::new(), vec![&looper, &dat])
+            .rounds(3)
+            .run()
+            .unwrap();
+
+        assert_eq!(3, results.rounds().len());
+        assert_eq!(3, results.wins(0));
+        assert_eq!(3, results.losses(1));
+        assert_eq!(0, results.ties(0) + results.ties(1));
+    }
+
+    #[test]
+    fn test_match_writes_previous_result_to_pspace()
+    {
+        use self::AddressingMode::*;
+
+        // dies unless location 0 of p-space holds 0, i.e. it lost last round
+        let switcher = Warrior::from(vec![
+            instr(OpCode::Ldp, Modifier::AB, Immediate, 0, Direct, 2),
+            instr(OpCode::Jmn, Modifier::B, Direct, 2, Direct, 1),
+            instr(OpCode::Jmp, Modifier::B, Direct, 0, Direct, 0),
+            InstructionStruct::default(),
+        ]);
+        let looper = looper();
+
+        let results = Match::new(
+            MarsBuilder::new().max_cycles(100),
+            vec![&switcher, &looper]
+            )
+            .rounds(3)
+            .run()
+            .unwrap();
+
+        let switcher_results: Vec<_> = results.rounds().iter()
+            .map(|r| r[0])
+            .collect();
+
+        assert_eq!(
+            vec![RoundResult::Loss, RoundResult::Tie, RoundResult::Loss],
+            switcher_results
+            );
+    }
+
+    #[test]
+    fn test_match_previous_result_not_shared_with_pin()
+    {
+        use self::AddressingMode::*;
+
+        // dies unless location 0 of p-space holds 0, i.e. it lost last round
+        let switcher = Warrior::from(vec![
+            instr(OpCode::Ldp, Modifier::AB, Immediate, 0, Direct, 2),
+            instr(OpCode::Jmn, Modifier::B, Direct, 2, Direct, 1),
+            instr(OpCode::Jmp, Modifier::B, Direct, 0, Direct, 0),
+            InstructionStruct::default(),
+        ]);
+
+        // the PIN of the looper is the Pid of the switcher, which has none
+        let mut looper = looper();
+        looper.set_pin(Some(0));
+
+        let results = Match::new(
+            MarsBuilder::new().max_cycles(100),
+            vec![&switcher, &looper]
+            )
+            .rounds(3)
+            .run()
+            .unwrap();
+
+        let switcher_results: Vec<_> = results.rounds().iter()
+            .map(|r| r[0])
+            .collect();
+
+        assert_eq!(
+            vec![RoundResult::Loss, RoundResult::Tie, RoundResult::Loss],
+            switcher_results
+            );
+    }
+
+    #[test]
+    fn test_match_positions_respect_min_distance()
+    {
+        let looper = looper();
+        let mut builder = MarsBuilder::new();
+        builder.size(300).min_distance(100);
+
+        let m = Match::new(&builder, vec![&looper, &looper, &looper]);
+        let mars: Mars<InstructionStruct> = builder.build();
+
+        assert_eq!(Ok(vec![0, 100, 200]), m.positions(&mars, 0));
+        assert_eq!(Ok(vec![0, 100, 200]), m.positions(&mars, 1));
+
+        builder.size(301);
+        let mars: Mars<InstructionStruct> = builder.build();
+        assert_eq!(Ok(vec![0, 101, 201]), m.positions(&mars, 1));
+
+        builder.size(299);
+        let mars: Mars<InstructionStruct> = builder.build();
+        assert_eq!(
+            Err(LoadError::InvalidDistance(0, 2)),
+            m.positions(&mars, 0)
+            );
+    }
+
+    #[test]
+    fn test_match_random_placement_is_reproducible()
+    {
+        let looper = looper();
+        let mut builder = MarsBuilder::new();
+        builder.max_cycles(10);
+
+        let results = Match::new(&builder, vec![&looper, &looper])
+            .placement(Placement::seeded(1234))
+            .rounds(5)
+            .run()
+            .unwrap();
+        assert_eq!(5, results.ties(0));
+
+        // the second round of a seed is replayed from its position
+        let mut placement = Placement::seeded(1234);
+        let mut mars: Mars<InstructionStruct> = builder.build();
+        let first = mars.load_placed(&[&looper, &looper], &mut placement);
+        mars.reset();
+        let second = mars.load_placed(&[&looper, &looper], &mut placement)
+            .unwrap();
+        assert_ne!(Ok(second.clone()), first);
+
+        let replay = builder.build_and_place(
+            &[&looper, &looper],
+            &mut Placement::Fixed(second[1])
+            )
+            .unwrap();
+        let mut pcs = replay.pcs();
+        pcs.sort();
+        assert_eq!(second, pcs);
+    }
+}
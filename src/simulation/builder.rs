@@ -4,28 +4,32 @@ use std::collections::{VecDeque, HashMap};
 
 use redcode::types::*;
 use redcode::traits::Instruction;
+use redcode::{Warrior, Standard};
+use redcode::defaults::*;
 
 use simulation::Mars;
 use simulation::LoadResult;
+use simulation::Placement;
 
-// Mars defaults
-const DEFAULT_SIZE: usize          = 8000;
-const DEFAULT_PSPACE_SIZE: usize   = 500;
-const DEFAULT_MAX_CYCLES: usize    = 80000;
-const DEFAULT_MAX_PROCESSES: usize = 8000;
-const DEFAULT_MAX_LENGTH: usize    = 100;
-const DEFAULT_MIN_DISTANCE: usize  = 100;
-const DEFAULT_VERSION: usize       = 80; // FIXME: hmmm
+#[cfg(feature = "parser")]
+use parser::Environment;
 
 /// Errors that can occur from invalid `MarsBuilder` configuration
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum BuilderError
 {
-    /// Program is longer than the core allows
+    /// Core has no memory
+    ZeroSize,
+
+    /// P-space size is not a divisor of the core size
+    InvalidPSpaceSize,
+
+    /// Programs can be longer than the minimum distance between them, so
+    /// one could be loaded over another
     ProgramTooLong,
 
-    /// A provided offset would violate a constraint of the `Mars`
-    InvalidOffset
+    /// Warriors cannot all be loaded `min_distance` apart in the core
+    TooManyWarriors,
 }
 
 /// A `Mars` builder. Provides control over how the `Mars` is
@@ -42,7 +46,7 @@ pub struct MarsBuilder
     /// Maximum number of cycles before game is considered a draw
     max_cycles:    usize,
 
-    /// Maximum number of processes that can be in the process queue
+    /// Maximum number of processes each warrior can have
     max_processes: usize,
 
     /// Maximum number of instructions a warrior can be comprised of
@@ -51,8 +55,25 @@ pub struct MarsBuilder
     /// Minimum distance between two warriors
     min_distance:  usize,
 
+    /// Number of warriors the core is meant to hold
+    warriors:      usize,
+
+    /// Distance from the program counter instructions can read at, the
+    /// whole core if `None`
+    read_limit:    Option<usize>,
+
+    /// Distance from the program counter instructions can write at, the
+    /// whole core if `None`
+    write_limit:   Option<usize>,
+
     /// Mars Version multiplied by 100
     version:       usize,
+
+    /// Redcode standard the Mars follows
+    standard:      Standard,
+
+    /// Should values be displayed between `-size/2` and `size/2`
+    signed_display: bool,
 }
 
 impl MarsBuilder
@@ -67,23 +88,156 @@ impl MarsBuilder
             max_processes: DEFAULT_MAX_PROCESSES,
             max_length:    DEFAULT_MAX_LENGTH,
             min_distance:  DEFAULT_MIN_DISTANCE,
-            version:       DEFAULT_VERSION
+            warriors:      DEFAULT_WARRIORS,
+            read_limit:    None,
+            write_limit:   None,
+            version:       DEFAULT_VERSION,
+            standard:      Standard::default(),
+            signed_display: false,
+        }
+    }
+
+    /// Configuration of the KOTH '94 no p-space hill: ICWS'94 draft without
+    /// `LDP` and `STP`, 8000 core
+    pub fn koth_94nop() -> Self
+    {
+        MarsBuilder {
+            pspace_size: 0,
+            standard:    Standard::Icws94Draft,
+            ..Self::new()
+        }
+    }
+
+    /// Configuration of the KOTH '94 draft hill: pMARS redcode, 8000 core
+    pub fn koth_94draft() -> Self
+    {
+        Self::new()
+    }
+
+    /// Configuration of the KOTH '88 hill: ICWS'88, 8000 core
+    pub fn koth_88() -> Self
+    {
+        MarsBuilder {
+            pspace_size: 0,
+            standard:    Standard::Icws88,
+            ..Self::new()
+        }
+    }
+
+    /// Configuration of the tiny hill: 800 core, 20 instruction warriors
+    pub fn tiny() -> Self
+    {
+        MarsBuilder {
+            size:          800,
+            pspace_size:   50,
+            max_cycles:    8000,
+            max_processes: 800,
+            max_length:    20,
+            min_distance:  20,
+            ..Self::new()
+        }
+    }
+
+    /// Configuration of the nano hill: 80 core, 5 instruction warriors
+    pub fn nano() -> Self
+    {
+        MarsBuilder {
+            size:          80,
+            pspace_size:   5,
+            max_cycles:    800,
+            max_processes: 80,
+            max_length:    5,
+            min_distance:  5,
+            ..Self::new()
+        }
+    }
+
+    /// Configuration of the limited process hill: 8000 core, 8 processes
+    /// per warrior
+    pub fn limited_process() -> Self
+    {
+        MarsBuilder {
+            max_processes: 8,
+            max_length:    200,
+            min_distance:  200,
+            ..Self::new()
+        }
+    }
+
+    /// Configuration of the experimental hill: 55440 core, 200 instruction
+    /// warriors
+    pub fn experimental() -> Self
+    {
+        MarsBuilder {
+            size:          55440,
+            pspace_size:   3465,
+            max_cycles:    500_000,
+            max_processes: 10000,
+            max_length:    200,
+            min_distance:  200,
+            ..Self::new()
         }
     }
 
     /// Build a core and load it with specified programs
-    pub fn build_and_load<T>(&self, programs: Vec<(Address, Option<Pin>, &Vec<T>)>) 
+    pub fn build_and_load<T>(&self, programs: Vec<(Address, Option<Pin>, &Warrior<T>)>)
         -> LoadResult<Mars<T>>
         where T: Instruction
     {
         let mut core = self.build();
-        if programs.len() > 0 {
+        if !programs.is_empty() {
             core.load_batch(programs)?;
         }
         Ok(core)
     }
 
-    /// Build a halted mars
+    /// Build a core and load warriors at positions chosen by a `Placement`
+    ///
+    /// # Arguments
+    /// * `warriors`: warriors to load, cannot be empty
+    /// * `placement`: placement choosing the position of each warrior
+    pub fn build_and_place<T>(
+        &self,
+        warriors:  &[&Warrior<T>],
+        placement: &mut Placement,
+        ) -> LoadResult<Mars<T>>
+        where T: Instruction
+    {
+        let mut core = self.build();
+        core.load_placed(warriors, placement)?;
+        Ok(core)
+    }
+
+    /// Build a halted mars after checking that its configuration is
+    /// consistent
+    ///
+    /// # Return
+    /// the `Mars` on success, otherwise the first constraint the
+    /// configuration violates
+    pub fn try_build<T>(&self) -> Result<Mars<T>, BuilderError>
+        where T: Instruction
+    {
+        if self.size == 0 {
+            return Err(BuilderError::ZeroSize);
+        }
+
+        // a size of 0 leaves warriors without p-space
+        if self.pspace_size > 0 && !self.size.is_multiple_of(self.pspace_size) {
+            return Err(BuilderError::InvalidPSpaceSize);
+        }
+
+        if self.max_length > self.min_distance {
+            return Err(BuilderError::ProgramTooLong);
+        }
+
+        if self.warriors * self.min_distance > self.size {
+            return Err(BuilderError::TooManyWarriors);
+        }
+
+        Ok(self.build())
+    }
+
+    /// Build a halted mars without checking its configuration
     pub fn build<T>(&self) -> Mars<T>
         where T: Instruction
     {
@@ -97,9 +251,16 @@ impl MarsBuilder
             memory:        mem,
             cycle:         0,
             process_queue: pq,
-            pspace:        pspace,
+            pspace,
+            pins:          HashMap::new(),
+            origins:       HashMap::new(),
+            deaths:        HashMap::new(),
             halted:        true,
             ir:            Default::default(),
+            a_reg:         Default::default(),
+            b_reg:         Default::default(),
+            a_addr:        0,
+            b_addr:        0,
             pc:            0,
             pid:           Default::default(),
 
@@ -107,13 +268,33 @@ impl MarsBuilder
             max_length:    self.max_length,
             min_distance:  self.min_distance,
 
+            // Range constraints
+            read_limit:    self.limit(self.read_limit),
+            write_limit:   self.limit(self.write_limit),
+
             // Mars information(const)
             version:       self.version,
+            standard:      self.standard,
             pspace_size:   self.pspace_size,
 
             // Runtime constraints
             max_processes: self.max_processes,
             max_cycles:    self.max_cycles,
+
+            signed_display: self.signed_display,
+        }
+    }
+
+    /// Range limit a `Mars` is built with. Limits of zero or larger than the
+    /// core cover the whole core
+    ///
+    /// # Arguments
+    /// * `limit`: configured limit
+    fn limit(&self, limit: Option<usize>) -> usize
+    {
+        match limit {
+            Some(n) if n > 0 && n < self.size => n,
+            _                                => self.size,
         }
     }
 
@@ -156,7 +337,8 @@ impl MarsBuilder
         self
     }
 
-    /// Maximum number of processes a core can have in it's process queue
+    /// Maximum number of processes each warrior can have, including the one
+    /// executing
     ///
     /// # Arguments
     /// * `n`: number of processes
@@ -195,7 +377,51 @@ impl MarsBuilder
         self
     }
 
-    /// Mars version multiplied by 100 (e.g. version 0.8 -> 80)
+    /// Number of warriors the core is meant to hold, checked by `try_build`
+    /// against the core size and minimum distance
+    ///
+    /// # Arguments
+    /// * `n`: number of warriors
+    ///
+    /// # Return
+    /// `Self`
+    pub fn warriors(&mut self, n: usize) -> &mut Self
+    {
+        self.warriors = n;
+        self
+    }
+
+    /// Distance from the program counter instructions can read at. Addresses
+    /// further away are folded into the range
+    ///
+    /// # Arguments
+    /// * `n`: number of instructions
+    ///
+    /// # Return
+    /// `Self`
+    pub fn read_limit(&mut self, n: usize) -> &mut Self
+    {
+        self.read_limit = Some(n);
+        self
+    }
+
+    /// Distance from the program counter instructions can write at.
+    /// Addresses further away are folded into the range
+    ///
+    /// # Arguments
+    /// * `n`: number of instructions
+    ///
+    /// # Return
+    /// `Self`
+    pub fn write_limit(&mut self, n: usize) -> &mut Self
+    {
+        self.write_limit = Some(n);
+        self
+    }
+
+    /// Mars version multiplied by 100 (e.g. version 0.8 -> 80), the value of
+    /// the `VERSION` constant. It has no effect on how the `Mars` behaves,
+    /// which is chosen with `standard` instead
     ///
     /// # Arguments
     /// * `version`: version number
@@ -207,6 +433,65 @@ impl MarsBuilder
         self.version = version;
         self
     }
+
+    /// Redcode standard deciding which instructions can be loaded and run,
+    /// and how `SLT`, `DJN` and `SPL` behave. Version numbers do not map to
+    /// standards, so the standard is set here rather than derived from
+    /// `version`
+    ///
+    /// # Arguments
+    /// * `standard`: redcode standard
+    ///
+    /// # Return
+    /// `Self`
+    pub fn standard(&mut self, standard: Standard) -> &mut Self
+    {
+        self.standard = standard;
+        self
+    }
+
+    /// Display values between `-size/2` and `size/2` rather than between `0`
+    /// and `size`. Values are always stored in the latter form
+    ///
+    /// # Arguments
+    /// * `signed`: should values be displayed signed
+    ///
+    /// # Return
+    /// `Self`
+    pub fn signed_display(&mut self, signed: bool) -> &mut Self
+    {
+        self.signed_display = signed;
+        self
+    }
+}
+
+impl Default for MarsBuilder
+{
+    fn default() -> Self
+    {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "parser")]
+impl<'a> From<&'a MarsBuilder> for Environment
+{
+    fn from(builder: &'a MarsBuilder) -> Self
+    {
+        let mut env = Environment::new();
+        env.core_size(builder.size)
+            .pspace_size(builder.pspace_size)
+            .max_cycles(builder.max_cycles)
+            .max_processes(builder.max_processes)
+            .max_length(builder.max_length)
+            .min_distance(builder.min_distance)
+            .read_limit(builder.limit(builder.read_limit))
+            .write_limit(builder.limit(builder.write_limit))
+            .version(builder.version)
+            .warriors(builder.warriors)
+            .standard(builder.standard);
+        env
+    }
 }
 
 #[cfg(test)]
@@ -221,7 +506,7 @@ mod test_builder
     fn test_build_mars_is_halted()
     {
         let mars: Mars<Instruction> = MarsBuilder::new().build();
-        assert_eq!(true, mars.halted());
+        assert!(mars.halted());
     }
 
     /// Verify that the `MarsBuilder` correctly sets the version in the `Mars`
@@ -305,5 +590,91 @@ mod test_builder
 
         assert_eq!(890, mars.min_distance());
     }
+
+    /// Verify that `try_build` accepts the default configuration
+    #[test]
+    fn test_try_build_default()
+    {
+        let mars: Result<Mars<Instruction>, _> = MarsBuilder::new().try_build();
+        assert!(mars.is_ok());
+    }
+
+    /// Verify that `try_build` rejects inconsistent configurations
+    #[test]
+    fn test_try_build_rejects_invalid_configuration()
+    {
+        fn try_build(builder: &MarsBuilder) -> Option<BuilderError>
+        {
+            builder.try_build::<Instruction>().err()
+        }
+
+        assert_eq!(
+            Some(BuilderError::ZeroSize),
+            try_build(MarsBuilder::new().size(0))
+            );
+        assert_eq!(
+            Some(BuilderError::InvalidPSpaceSize),
+            try_build(MarsBuilder::new().pspace_size(300))
+            );
+        assert_eq!(None, try_build(MarsBuilder::new().pspace_size(0)));
+        assert_eq!(
+            Some(BuilderError::ProgramTooLong),
+            try_build(MarsBuilder::new().max_length(101))
+            );
+        assert_eq!(
+            Some(BuilderError::TooManyWarriors),
+            try_build(MarsBuilder::new().warriors(81))
+            );
+        assert_eq!(None, try_build(MarsBuilder::new().warriors(80)));
+    }
+
+    /// Verify that the hill presets are consistent and set their values
+    #[test]
+    fn test_presets()
+    {
+        let presets = vec![
+            MarsBuilder::koth_94nop(),
+            MarsBuilder::koth_94draft(),
+            MarsBuilder::koth_88(),
+            MarsBuilder::tiny(),
+            MarsBuilder::nano(),
+            MarsBuilder::limited_process(),
+            MarsBuilder::experimental(),
+        ];
+        for preset in presets {
+            assert!(preset.try_build::<Instruction>().is_ok());
+        }
+
+        let nano: Mars<Instruction> = MarsBuilder::nano().build();
+        assert_eq!(80, nano.size());
+        assert_eq!(5, nano.max_length());
+
+        let koth_88: Mars<Instruction> = MarsBuilder::koth_88().build();
+        assert_eq!(Standard::Icws88, koth_88.standard());
+        assert_eq!(8000, koth_88.size());
+    }
+
+    /// Verify that warriors parsed for a `MarsBuilder` see its settings in
+    /// their `;assert` comments
+    #[cfg(feature = "parser")]
+    #[test]
+    fn test_environment_from_builder()
+    {
+        use parser::parse_warrior_with;
+
+        let src = ";assert CORESIZE == 890 && MAXLENGTH == 10 && WARRIORS == 3\n\
+                   ;assert READLIMIT == 890 && WRITELIMIT == 50\n\
+                   DAT.F 0";
+
+        let env = Environment::from(&*MarsBuilder::new()
+            .size(890)
+            .max_length(10)
+            .warriors(3)
+            .write_limit(50));
+        assert!(parse_warrior_with::<Instruction>(src, &env).is_ok());
+
+        let env = Environment::from(&MarsBuilder::new());
+        assert!(parse_warrior_with::<Instruction>(src, &env).is_err());
+    }
 }
 
@@ -3,6 +3,9 @@ use std::collections::{VecDeque, HashMap};
 
 use redcode::types::*;
 use redcode::traits;
+use redcode::{Warrior, Standard};
+
+use simulation::Placement;
 
 pub type SimulationResult<T> = Result<T, SimulationError>;
 pub type LoadResult<T> = Result<T, LoadError>;
@@ -15,18 +18,28 @@ pub enum SimulationError
     Halted,
 }
 
-/// Errors that can occur during loading
+/// Errors that can occur during loading. Warriors are identified by the
+/// `Pid` they have, or would have been given
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum LoadError
 {
-    /// Validation error: program has invalid length
-    InvalidLength,
+    /// Validation error: program of the warrior is empty or longer than the
+    /// maximum length
+    InvalidLength(usize),
 
-    /// Validation error: invalid distance between programs
-    InvalidDistance,
+    /// Validation error: the two warriors are loaded closer to each other
+    /// than the minimum distance
+    InvalidDistance(usize, usize),
 
     /// Load cannot be called with no programs
-    EmptyLoad
+    EmptyLoad,
+
+    /// Validation error: program of the warrior holds an instruction the
+    /// standard of the `Mars` does not allow
+    IllegalInstruction(usize),
+
+    /// Validation error: start of the warrior is outside of its program
+    InvalidStart(usize),
 }
 
 /// Events that can happen during a running simulation
@@ -42,8 +55,15 @@ pub enum SimulationEvent
     /// A process terminated
     Terminated,
 
-    /// The Mars halted
-    Halted,
+    /// The last warrior running ran out of processes and the Mars halted
+    Halted
+    {
+        /// Id of the warrior
+        pid:   Pid,
+
+        /// Cycle the warrior ran out of processes on
+        cycle: usize,
+    },
 
     /// A process jumped address
     Jumped,
@@ -53,6 +73,57 @@ pub enum SimulationEvent
 
     /// Nothing happened
     Stepped,
+
+    /// A warrior ran out of processes, leaving a single one running. The
+    /// Mars halts
+    Won
+    {
+        /// Id of the survivor
+        winner: Pid,
+
+        /// Id of the warrior that ran out of processes
+        died:   Pid,
+
+        /// Cycle the warrior ran out of processes on
+        cycle:  usize,
+    },
+
+    /// A warrior's last process terminated while others are still running.
+    /// Deaths that end the simulation are reported by `Won` or `Halted`
+    WarriorDied
+    {
+        /// Id of the warrior
+        pid:   Pid,
+
+        /// Cycle the warrior ran out of processes on
+        cycle: usize,
+    },
+}
+
+/// Outcome of a simulation once the Mars has halted
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MatchOutcome
+{
+    /// A single warrior was left running
+    Win(Pid),
+
+    /// More than one warrior was still running when the cycles ran out
+    Tie,
+
+    /// No warriors were left running
+    AllDead,
+}
+
+/// Key of a p-space. Warriors loaded with the same PIN share a p-space, and
+/// every warrior loaded without one has a private p-space
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum PSpaceKey
+{
+    /// P-space shared by the warriors loaded with this PIN
+    Pin(Pin),
+
+    /// P-space of the warrior with this `Pid`, loaded without a PIN
+    Private(Pid),
 }
 
 /// Core wars runtime
@@ -66,6 +137,18 @@ pub struct Mars<T>
     /// Instruction register
     pub(super) ir:            T,
 
+    /// Copy of the instruction the A operand points at
+    pub(super) a_reg:         T,
+
+    /// Copy of the instruction the B operand points at
+    pub(super) b_reg:         T,
+
+    /// Address the A operand reads from
+    pub(super) a_addr:        Address,
+
+    /// Address the B operand writes to
+    pub(super) b_addr:        Address,
+
     /// Current Pid executing on the Mars
     pub(super) pid:           Pid,
 
@@ -79,7 +162,16 @@ pub struct Mars<T>
     pub(super) process_queue: VecDeque<(Pid, VecDeque<Address>)>,
 
     /// Private storage space for warriors
-    pub(super) pspace:        HashMap<Pin, Vec<Value>>,
+    pub(super) pspace:        HashMap<PSpaceKey, Vec<Value>>,
+
+    /// Key of the p-space of each loaded warrior
+    pub(super) pins:          HashMap<Pid, PSpaceKey>,
+
+    /// Address each warrior that was not unloaded was loaded at
+    pub(super) origins:       HashMap<Pid, Address>,
+
+    /// Cycle each warrior ran out of processes on
+    pub(super) deaths:        HashMap<Pid, usize>,
 
     /// Has the core finished executing
     pub(super) halted:        bool,
@@ -91,25 +183,40 @@ pub struct Mars<T>
     /// Minimum distance between programs when batch loading
     pub(super) min_distance:  usize,
 
+    // Range constraints
+    /// Distance from the program counter instructions can read at
+    pub(super) read_limit:    usize,
+
+    /// Distance from the program counter instructions can write at
+    pub(super) write_limit:   usize,
+
     // Mars information (const)
     /// Mars version
     pub(super) version:       usize,
 
+    /// Redcode standard the Mars follows
+    pub(super) standard:      Standard,
+
     /// Size of P-space
     pub(super) pspace_size:   usize,
 
     // Runtime constraints
-    /// Maximum of processes that can be on the process queue at any time
+    /// Maximum of processes each warrior can have at any time
     pub(super) max_processes: usize,
 
     /// Maximum number of cycles that can pass before a tie is declared
     pub(super) max_cycles:    usize,
+
+    /// Should values be displayed between `-size/2` and `size/2`
+    pub(super) signed_display: bool,
 }
 
 impl<T> Mars<T>
 where T: traits::Instruction
 {
-    /// Step forward one cycle
+    /// Step forward one cycle. Every warrior that runs out of processes is
+    /// reported, by `WarriorDied` or by the `Won` or `Halted` ending the
+    /// simulation
     pub fn step(&mut self) -> SimulationResult<SimulationEvent>
     {
         if self.halted() { // can't step after the core is halted
@@ -125,95 +232,55 @@ where T: traits::Instruction
 
         // Fetch instruction
         self.ir = self.fetch(pc);
-        let (a_mode, b_mode) = (self.ir.a_mode(), self.ir.b_mode());
-
-        // PostIncrement phase
-        let predecrement = a_mode == AddressingMode::AIndirectPreDecrement ||
-            a_mode == AddressingMode::BIndirectPreDecrement ||
-            b_mode == AddressingMode::AIndirectPreDecrement ||
-            b_mode == AddressingMode::BIndirectPreDecrement;
-
-        // Preincrement phase
-        if predecrement {
-            // fetch direct target
-            let a_addr = self.calc_addr_offset(pc, self.ir.a());
-            let b_addr = self.calc_addr_offset(pc, self.ir.b());
-            let mut a = self.fetch(a_addr);
-            let mut b = self.fetch(b_addr);
-
-            let (a_a, a_b) = (a.a(), a.b());
-            let (b_a, b_b) = (b.a(), b.b());
-
-            // FIXME: combine these into a single match statement
-            match a_mode {
-                AddressingMode::AIndirectPreDecrement => { a.set_a(a_a + 1); }
-                AddressingMode::BIndirectPreDecrement => { a.set_b(a_b + 1); }
-                _ => {}
-            };
-
-            match b_mode {
-                AddressingMode::AIndirectPreDecrement => { b.set_a(b_a + 1); }
-                AddressingMode::BIndirectPreDecrement => { b.set_b(b_b + 1); }
-                _ => {}
-            };
-
-            self.store(a_addr, a);
-            self.store(b_addr, b);
-        }
-
-        // Execute instruction(updating the program counter and requeing it
-        // are handled in this phase)
-        let exec_event = self.execute();
-
-        // PostIncrement phase
-        let postincrement = a_mode == AddressingMode::AIndirectPostIncrement ||
-            a_mode == AddressingMode::BIndirectPostIncrement ||
-            b_mode == AddressingMode::AIndirectPostIncrement ||
-            b_mode == AddressingMode::BIndirectPostIncrement;
-
-        if postincrement {
-            // fetch direct target
-            let a_addr = self.calc_addr_offset(pc, self.ir.a());
-            let b_addr = self.calc_addr_offset(pc, self.ir.b());
-            let mut a = self.fetch(a_addr);
-            let mut b = self.fetch(b_addr);
-
-            let (a_a, a_b) = (a.a(), a.b());
-            let (b_a, b_b) = (b.a(), b.b());
-
-            // FIXME: combine these into a single match statement
-            match a_mode {
-                AddressingMode::AIndirectPreDecrement => { a.set_a(a_a + 1); }
-                AddressingMode::BIndirectPreDecrement => { a.set_b(a_b + 1); }
-                _ => {}
-            };
 
-            match b_mode {
-                AddressingMode::AIndirectPreDecrement => { b.set_a(b_a + 1); }
-                AddressingMode::BIndirectPreDecrement => { b.set_b(b_b + 1); }
-                _ => {}
-            };
-            // store result
-            self.store(a_addr, a);
-            self.store(b_addr, b);
-        }
+        // Instructions the standard does not allow terminate the process
+        // like `dat`
+        let exec_event = if self.standard.is_legal(&self.ir) {
+            // Evaluate the A operand, along with its increments, before the
+            // B operand so that the B operand sees its side effects
+            let (a_addr, _, a_reg) = self.evaluate_operand(true);
+            let (_, b_addr, b_reg) = self.evaluate_operand(false);
+            self.a_addr = a_addr;
+            self.a_reg  = a_reg;
+            self.b_addr = b_addr;
+            self.b_reg  = b_reg;
+
+            // Execute instruction(updating the program counter and requeing
+            // it are handled in this phase)
+            self.execute()
+        } else {
+            SimulationEvent::Terminated
+        };
 
         // check if there are any more process queues running on the core
         let (pid, q) = self.process_queue.pop_front().unwrap();
-        if !q.is_empty() {
+        let (died, cycle) = (q.is_empty(), self.cycle);
+        if died {
+            self.deaths.insert(pid, cycle);
+        } else {
             self.process_queue.push_back((pid, q));
         }
 
         // If no there are no processes left
         if self.process_queue.is_empty() {
-            Ok(self.halt())
-        } else {
-            // Fetch new queue
-            let &mut(curr_pid, ref mut curr_q) = self.process_queue.front_mut().unwrap();
-            self.pid = curr_pid;
-            self.pc = curr_q.pop_front().unwrap();
-            self.cycle += 1;
+            self.halted = true;
+            return Ok(SimulationEvent::Halted { pid, cycle });
+        }
+
+        // Fetch new queue
+        let &mut(curr_pid, ref mut curr_q) = self.process_queue.front_mut().unwrap();
+        self.pid = curr_pid;
+        self.pc = curr_q.pop_front().unwrap();
+        self.cycle += 1;
+
+        if !died {
             Ok(exec_event)
+        } else if self.pins.len() > 1 && self.process_queue.len() == 1 {
+            // the last warrior standing wins
+            self.halted = true;
+            Ok(SimulationEvent::Won { winner: curr_pid, died: pid, cycle })
+        } else {
+            Ok(SimulationEvent::WarriorDied { pid, cycle })
         }
     }
 
@@ -224,12 +291,59 @@ where T: traits::Instruction
         self.halted
     }
 
-    /// Halt the Mars
-    #[inline]
-    fn halt(&mut self) -> SimulationEvent
+    /// Outcome of the simulation. A lone warrior still running when the
+    /// cycles run out is counted as a win
+    ///
+    /// # Return
+    /// `None` if the Mars is still running or nothing is loaded, otherwise
+    /// the outcome decided by the warriors left running
+    pub fn outcome(&self) -> Option<MatchOutcome>
+    {
+        if !self.halted() || self.pins.is_empty() {
+            return None;
+        }
+
+        match self.process_queue.len() {
+            0 => Some(MatchOutcome::AllDead),
+            1 => Some(MatchOutcome::Win(self.process_queue[0].0)),
+            _ => Some(MatchOutcome::Tie),
+        }
+    }
+
+    /// Cycle a warrior ran out of processes on
+    ///
+    /// # Arguments
+    /// * `pid`: id of the warrior
+    ///
+    /// # Return
+    /// `None` if the warrior is still running or was never loaded
+    pub fn death_cycle(&self, pid: Pid) -> Option<usize>
+    {
+        self.deaths.get(&pid).cloned()
+    }
+
+    /// Get the `Pid`s of every loaded warrior that still has processes
+    pub fn alive_warriors(&self) -> Vec<Pid>
+    {
+        let mut alive: Vec<Pid> = self.process_queue.iter()
+            .map(|&(pid, _)| pid)
+            .collect();
+        alive.sort();
+        alive
+    }
+
+    /// Get the number of processes a warrior has, including the one
+    /// currently executing
+    ///
+    /// # Arguments
+    /// * `pid`: id of the warrior
+    pub fn processes_of(&self, pid: Pid) -> usize
     {
-        self.halted = true;
-        SimulationEvent::Halted
+        self.process_queue.iter()
+            .enumerate()
+            .filter(|&(_, &(p, _))| p == pid)
+            .map(|(i, (_, q))| if i == 0 { q.len() + 1 } else { q.len() })
+            .sum()
     }
 
     /// Reset the Mars's memory and the process queue
@@ -241,13 +355,19 @@ where T: traits::Instruction
         }
 
         self.process_queue.clear();
+        self.pins.clear();
+        self.origins.clear();
+        self.deaths.clear();
 
         self.cycle         = 0;
         self.ir            = Default::default();
+        self.a_reg         = Default::default();
+        self.b_reg         = Default::default();
         self.halted        = true;
     }
 
-    /// Reset the Mar's memory, process queue, AND P-space
+    /// Reset the Mar's memory, process queue, AND P-space. P-space is kept by
+    /// `reset` so that it survives from one round to the next
     pub fn reset_hard(&mut self)
     {
         self.pspace.clear();
@@ -255,59 +375,147 @@ where T: traits::Instruction
     }
 
     /// Load mutliple programs into the Mars, checking their spacing and their
-    /// length
+    /// length, against each other and against the warriors already loaded.
+    /// Destinations outside of the core are folded back into it. Warriors
+    /// are given consecutive `Pid`s and run in the order they are loaded,
+    /// each one's first process starting at the warrior's start offset.
+    /// Warriors loaded with the same PIN share their p-space, which is
+    /// created the first time a PIN is seen
     /// # Arguments
     /// * `programs`: programs and load information loaded in a tuple, cannot
-    ///     be empty
+    ///   be empty. A program loaded without a PIN uses the warrior's own, or
+    ///   gets a private p-space if it has none
     /// # Return
     /// `Ok(())` if the load was successful, otherwise an error with the 
-    ///     corresponding `SimulationError`
-    pub fn load_batch(&mut self, programs: Vec<(Address, Option<Pin>, &Vec<T>)>)
+    ///     corresponding `LoadError`
+    pub fn load_batch(&mut self, programs: Vec<(Address, Option<Pin>, &Warrior<T>)>)
         -> LoadResult<()>
     {
-        // TODO: validate margin
-        // TODO: correct addresses that are out of bounds by modulo-ing them
         if programs.is_empty() {
             return Err(LoadError::EmptyLoad);
         }
 
-        let valid_margin = true; // TODO
+        let size = self.size() as Address;
+        let first_pid = self.pins.len();
 
-        if valid_margin {
-            // load each program
-            for &(dest, maybe_pin, ref prog) in programs.iter() {
-                let pin = maybe_pin.unwrap_or(self.process_count() as Pid);
+        // every warrior the new ones have to keep their distance from
+        let mut placed: Vec<(usize, Address)> = self.origins.iter()
+            .map(|(&pid, &origin)| (pid as usize, origin))
+            .collect();
+        placed.sort();
 
-                let cycle_memory_iter = (0..self.size())
-                    .cycle()
-                    .skip(dest as usize)
-                    .take(prog.len())
-                    .enumerate();
+        for (i, &(dest, _, prog)) in programs.iter().enumerate() {
+            let (pid, dest) = (first_pid + i, dest % size);
+            self.check_program(pid, prog)?;
 
-                // copy program into memory
-                for (i, j) in cycle_memory_iter {
-                    self.memory[j] = prog[i].clone();
+            for &(other, origin) in placed.iter() {
+                if self.distance(origin, dest) < self.min_distance {
+                    return Err(LoadError::InvalidDistance(other, pid));
                 }
+            }
+            placed.push((pid, dest));
+        }
 
-                self.pspace.insert(pin, vec![0; self.pspace_size]);
+        let was_empty = self.process_queue.is_empty();
 
-                let mut q = VecDeque::new();
-                q.push_front(dest);
-                self.process_queue.push_front((pin, q));
-            }
+        for &(dest, maybe_pin, prog) in programs.iter() {
+            self.place(dest % size, maybe_pin, prog);
+        }
 
-            self.halted = false;
+        self.start(was_empty);
+        Ok(())
+    }
 
-            let &mut (curr_pid, ref mut curr_q) = self.process_queue.front_mut()
-                .unwrap();
+    /// Load a single program into the Mars, checking its length and its
+    /// distance to the warriors already loaded. The Mars can be running, in
+    /// which case the new warrior runs after the others
+    ///
+    /// # Arguments
+    /// * `dest`: address to load the program at, folded into the core
+    /// * `pin`: p-space identifier of the warrior. A program loaded without
+    ///   one uses the warrior's own, or gets a private p-space if it has none
+    /// * `prog`: program to load
+    ///
+    /// # Return
+    /// `Pid` of the loaded warrior if the load was successful, otherwise an
+    /// error with the corresponding `LoadError`
+    pub fn load(&mut self, dest: Address, pin: Option<Pin>, prog: &Warrior<T>)
+        -> LoadResult<Pid>
+    {
+        let pid = self.pins.len() as Pid;
+        self.load_batch(vec![(dest, pin, prog)])?;
+        Ok(pid)
+    }
 
-            self.pc = curr_q.pop_front().unwrap();
-            self.pid = curr_pid;
-                
-            Ok(())
-        } else {
-            Err(LoadError::InvalidDistance)
+    /// Remove a warrior from the Mars, killing all of its processes. Its code
+    /// is left in memory and its `Pid` is not given to another warrior, but
+    /// others can be loaded closer to where it was
+    ///
+    /// # Arguments
+    /// * `pid`: id of the warrior
+    ///
+    /// # Return
+    /// `true` if the warrior was loaded, otherwise `false`
+    pub fn unload(&mut self, pid: Pid) -> bool
+    {
+        if self.origins.remove(&pid).is_none() {
+            return false;
+        }
+
+        let running = self.process_queue.iter().position(|&(p, _)| p == pid);
+        if let Some(i) = running {
+            self.process_queue.remove(i);
+            self.deaths.insert(pid, self.cycle);
+
+            // the process holding the program counter was killed
+            if i == 0 && !self.process_queue.is_empty() {
+                let &mut (curr_pid, ref mut curr_q) = self.process_queue
+                    .front_mut()
+                    .unwrap();
+                self.pid = curr_pid;
+                self.pc = curr_q.pop_front().unwrap();
+            }
+        }
+
+        // the last warrior standing wins
+        if self.process_queue.is_empty()
+            || (self.pins.len() > 1 && self.process_queue.len() == 1)
+        {
+            self.halted = true;
         }
+
+        true
+    }
+
+    /// Load warriors at positions chosen by a `Placement`, checking their
+    /// length like `load_batch`. Warriors are loaded with their own PIN
+    ///
+    /// # Arguments
+    /// * `warriors`: warriors to load, cannot be empty
+    /// * `placement`: placement choosing the position of each warrior
+    ///
+    /// # Return
+    /// position of each warrior if the load was successful, otherwise an
+    /// error with the corresponding `LoadError`
+    pub fn load_placed(
+        &mut self,
+        warriors:  &[&Warrior<T>],
+        placement: &mut Placement,
+        ) -> LoadResult<Vec<Address>>
+    {
+        let positions = placement.positions(
+            warriors.len(),
+            self.size(),
+            self.min_distance
+            )?;
+
+        let programs = positions.iter()
+            .zip(warriors.iter())
+            .map(|(&dest, &warrior)| (dest, None, warrior))
+            .collect();
+        self.load_batch(programs)?;
+
+        Ok(positions)
     }
 
     /// Get `Pid` currently executing on the core
@@ -325,7 +533,7 @@ where T: traits::Instruction
     {
         let mut pcs = vec![self.pc()];
 
-        for &(_, ref q) in &self.process_queue {
+        for (_, q) in &self.process_queue {
             pcs.extend(q.iter().cloned());
         }
 
@@ -375,7 +583,15 @@ where T: traits::Instruction
         self.version
     }
 
-    /// Maximum number of processes that can be in the core queue
+    /// Redcode standard the core follows
+    #[inline]
+    pub fn standard(&self) -> Standard
+    {
+        self.standard
+    }
+
+    /// Maximum number of processes each warrior can have, including the one
+    /// executing
     #[inline]
     pub fn max_processes(&self) -> usize
     {
@@ -402,6 +618,56 @@ where T: traits::Instruction
         self.min_distance
     }
 
+    /// Distance from the program counter instructions can read at
+    #[inline]
+    pub fn read_limit(&self) -> usize
+    {
+        self.read_limit
+    }
+
+    /// Distance from the program counter instructions can write at
+    #[inline]
+    pub fn write_limit(&self) -> usize
+    {
+        self.write_limit
+    }
+
+    /// Are values displayed between `-size/2` and `size/2` rather than
+    /// between `0` and `size`
+    #[inline]
+    pub fn signed_display(&self) -> bool
+    {
+        self.signed_display
+    }
+
+    /// Convert a value held in memory to the form it is displayed in
+    ///
+    /// # Arguments
+    /// * `value`: value between `0` and `size`
+    pub fn display_value(&self, value: Value) -> Value
+    {
+        let size = self.size() as Value;
+        if self.signed_display && value > size / 2 {
+            value - size
+        } else {
+            value
+        }
+    }
+
+    /// Get a copy of an instruction in memory with its fields in the form
+    /// they are displayed in
+    ///
+    /// # Arguments
+    /// * `addr`: address of the instruction
+    pub fn display_instruction(&self, addr: Address) -> T
+    {
+        let mut instr = self.fetch(addr);
+        let (a, b) = (instr.a(), instr.b());
+        instr.set_a(self.display_value(a));
+        instr.set_b(self.display_value(b));
+        instr
+    }
+
     /// Get immutable reference to memory
     pub fn memory(&self) -> &Vec<T>
     {
@@ -409,21 +675,57 @@ where T: traits::Instruction
     }
 
     /// Get an immutable reference to private storage
-    pub fn pspace(&self) -> &HashMap<Pin, Vec<Value>>
+    pub fn pspace(&self) -> &HashMap<PSpaceKey, Vec<Value>>
     {
         &self.pspace
     }
 
+    /// Get the PIN of a loaded warrior
+    ///
+    /// # Arguments
+    /// * `pid`: id of the warrior
+    ///
+    /// # Return
+    /// `None` if the warrior is not loaded or has a private p-space
+    pub fn pin(&self, pid: Pid) -> Option<Pin>
+    {
+        match self.pins.get(&pid) {
+            Some(&PSpaceKey::Pin(pin)) => Some(pin),
+            _                          => None,
+        }
+    }
+
+    /// Get the key of the p-space of a loaded warrior
+    ///
+    /// # Arguments
+    /// * `pid`: id of the warrior
+    pub fn pspace_key(&self, pid: Pid) -> Option<PSpaceKey>
+    {
+        self.pins.get(&pid).cloned()
+    }
+
+    /// Record the result of the previous round in location 0 of a p-space.
+    /// By convention this is `0` for a loss and the number of surviving
+    /// warriors otherwise
+    ///
+    /// # Arguments
+    /// * `key`: key of the p-space
+    /// * `result`: result of the round
+    pub fn set_last_result(&mut self, key: PSpaceKey, result: Value)
+    {
+        self.store_pspace(key, 0, result);
+    }
+
     /// Get the number of processes currently running
     pub fn process_count(&self) -> usize
     {
-        self.process_queue.iter().map(|&(_, ref q)| q.len()).sum()
+        self.process_queue.iter().map(|(_, q)| q.len()).sum()
     }
 
     /// Fetch reference to current queue
     pub fn current_queue(&self) -> Option<&VecDeque<Address>>
     {
-        if let Some(&(_, ref q)) = self.process_queue.front() {
+        if let Some((_, q)) = self.process_queue.front() {
             Some(q)
         } else {
             None
@@ -478,62 +780,146 @@ where T: traits::Instruction
     #[inline]
     fn calc_addr_offset(&self, base: Address, offset: Value) -> Address
     {
-        if offset < 0 {
-            (base.wrapping_sub(-offset as Address) % self.size() as Address)
+        self.normalize(base as i64 + offset as i64) as Address
+    }
+
+    /// Reduce a value modulo the size of memory, giving a value between `0`
+    /// and `size`
+    ///
+    /// # Arguments
+    /// * `value`: value to reduce
+    #[inline]
+    fn normalize(&self, value: i64) -> Value
+    {
+        value.rem_euclid(self.size() as i64) as Value
+    }
+
+    /// Distance between two addresses going the shorter way around the core
+    ///
+    /// # Arguments
+    /// * `first`: first address, in the core
+    /// * `second`: second address, in the core
+    fn distance(&self, first: Address, second: Address) -> usize
+    {
+        let d = (first as i64 - second as i64).unsigned_abs() as usize;
+        d.min(self.size() - d)
+    }
+
+    /// Fold an offset from the program counter into the window allowed by a
+    /// range limit. Offsets up to half the limit reach forward, the others
+    /// reach backward
+    ///
+    /// # Arguments
+    /// * `offset`: distance from the program counter
+    /// * `limit`: size of the window
+    ///
+    /// # Return
+    /// the folded offset, between `0` and `size`
+    #[inline]
+    fn fold(&self, offset: i64, limit: usize) -> i64
+    {
+        let limit = limit as i64;
+        let folded = offset.rem_euclid(limit);
+
+        if folded > limit / 2 {
+            folded + self.size() as i64 - limit
         } else {
-            (base.wrapping_add(offset as Address) % self.size() as Address)
+            folded
         }
     }
 
-    /// Get the effective of address of the current `Instruction`. This takes
-    /// into account the addressing mode of the field used
+    /// Evaluate an operand of the `Instruction` register, following the
+    /// pointer of indirect modes. Reads and writes follow the pointer
+    /// separately so that each stays within its range limit. A pre-decrement
+    /// is stored before the target is copied, a post-increment after
     ///
     /// # Arguments
-    /// * `use_a_field`: should the A field be used for calculation, or B
-    #[inline]
-    fn effective_addr(&self, use_a_field: bool) -> Address
+    /// * `use_a_field`: should the A operand be evaluated, or B
+    ///
+    /// # Return
+    /// addresses the operand reads from and writes to along with a copy of
+    /// the instruction read
+    fn evaluate_operand(&mut self, use_a_field: bool)
+        -> (Address, Address, T)
     {
         use self::AddressingMode::*;
 
         // fetch the addressing mode and offset
-        let (mode, offset) = if use_a_field { 
+        let (mode, offset) = if use_a_field {
             (self.ir.a_mode(), self.ir.a())
         } else {
             (self.ir.b_mode(), self.ir.b())
         };
 
         let pc = self.pc();
+        if mode == Immediate {
+            return (pc, pc, self.fetch(pc));
+        }
+
+        let (read_limit, write_limit) = (self.read_limit, self.write_limit);
+        let mut read  = self.fold(offset as i64, read_limit);
+        let mut write = self.fold(offset as i64, write_limit);
+
+        // pointers are updated through the write offset
+        let pointer_addr = self.calc_addr_offset(pc, write as Value);
+
+        if mode != Direct {
+            let mut pointer = self.fetch(pointer_addr);
+
+            let (a, b) = (pointer.a(), pointer.b());
+            match mode {
+                AIndirectPreDecrement =>
+                    pointer.set_a(self.normalize(a as i64 - 1)),
+                BIndirectPreDecrement =>
+                    pointer.set_b(self.normalize(b as i64 - 1)),
+                _ => {}
+            };
+            if mode == AIndirectPreDecrement || mode == BIndirectPreDecrement {
+                self.store(pointer_addr, pointer.clone());
+            }
+
+            let read_pointer = self.fetch(self.calc_addr_offset(pc,
+                read as Value));
+            let (read_field, write_field) = match mode {
+                AIndirect
+                    | AIndirectPreDecrement
+                    | AIndirectPostIncrement => (read_pointer.a(), pointer.a()),
+                _ => (read_pointer.b(), pointer.b()),
+            };
 
-        let direct = self.fetch(self.calc_addr_offset(pc, offset));
-
-        match mode {
-            Immediate => pc,
-            Direct => self.calc_addr_offset(pc, offset),
-            AIndirect
-                | AIndirectPreDecrement
-                | AIndirectPostIncrement =>
-                self.calc_addr_offset(pc, direct.a() + offset),
-            BIndirect
-                | BIndirectPreDecrement
-                | BIndirectPostIncrement =>
-                self.calc_addr_offset(pc, direct.b() + offset),
+            read  = self.fold(read + read_field as i64, read_limit);
+            write = self.fold(write + write_field as i64, write_limit);
         }
+
+        let read_addr  = self.calc_addr_offset(pc, read as Value);
+        let write_addr = self.calc_addr_offset(pc, write as Value);
+        let copy = self.fetch(read_addr);
+
+        if mode == AIndirectPostIncrement || mode == BIndirectPostIncrement {
+            // the pointer is fetched again as it may be the target itself
+            let mut pointer = self.fetch(pointer_addr);
+            let (a, b) = (pointer.a(), pointer.b());
+            if mode == AIndirectPostIncrement {
+                pointer.set_a(self.normalize(a as i64 + 1));
+            } else {
+                pointer.set_b(self.normalize(b as i64 + 1));
+            }
+            self.store(pointer_addr, pointer);
+        }
+
+        (read_addr, write_addr, copy)
     }
 
-    /// Get the effective of address of the current `Instruction`'s A Field
-    ///
-    /// An alias for `Mars::effective_addr(true)`
+    /// Get the address the current `Instruction`'s A operand reads from
     fn effective_addr_a(&self) -> Address
     {
-        self.effective_addr(true)
+        self.a_addr
     }
 
-    /// Get the effective of address of the current `Instruction`'s A Field
-    ///
-    /// An alias for `Mars::effective_addr(false)`
+    /// Get the address the current `Instruction`'s B operand writes to
     fn effective_addr_b(&self) -> Address
     {
-        self.effective_addr(false)
+        self.b_addr
     }
 
     ////////////////////////////////////////////////////////////////////////////
@@ -556,14 +942,13 @@ where T: traits::Instruction
         SimulationEvent::Skipped
     }
 
-    /// Jump the program counter by an offset
+    /// Jump the program counter to an address
     ///
     /// # Arguments
-    /// * `offset`: amount to jump
-    fn jump_pc(&mut self, offset: Value) -> SimulationEvent
+    /// * `target`: address to jump to
+    fn jump_pc(&mut self, target: Address) -> SimulationEvent
     {
-        let pc = self.pc();
-        self.pc = self.calc_addr_offset(pc, offset);
+        self.pc = target % self.size() as Address;
         SimulationEvent::Jumped
     }
 
@@ -589,16 +974,15 @@ where T: traits::Instruction
         SimulationEvent::Skipped
     }
 
-    /// Jump the program counter by an offset and then queue the program
+    /// Jump the program counter to an address and then queue the program
     /// count onto the current queue
     ///
     /// # Arguments
-    /// * `offset`: amount to jump by
-    fn jump_and_queue_pc(&mut self, offset: Value) -> SimulationEvent
+    /// * `target`: address to jump to
+    fn jump_and_queue_pc(&mut self, target: Address) -> SimulationEvent
     {
-        self.jump_pc(offset);
-        
-        // remove old pc
+        self.jump_pc(target);
+
         let pc = self.pc();
         self.current_queue_mut().unwrap().push_back(pc);
         SimulationEvent::Jumped
@@ -619,32 +1003,120 @@ where T: traits::Instruction
         self.memory[addr as usize % mem_size] = instr;
     }
 
-    /// Store an instruction in a specified pspace
+    /// Check that a program can be loaded into the Mars
     ///
     /// # Arguments
-    /// * `pin`: programs pin, used as a lookup key
-    /// * `addr`: address in the pspace to store
-    /// * `instr`: instruction to store
-    fn store_pspace(&mut self, pin: Pin, addr: Address, value: Value)
-    {
-        if let Some(pspace) = self.pspace.get_mut(&pin) {
-            let pspace_size = pspace.len();
-            pspace[addr as usize % pspace_size] = value;
-        }  else {
-            // TODO: create pspace for pin
-            unimplemented!();
+    /// * `pid`: `Pid` the warrior would be given
+    /// * `prog`: program to check
+    fn check_program(&self, pid: usize, prog: &Warrior<T>) -> LoadResult<()>
+    {
+        if prog.is_empty() || prog.len() > self.max_length {
+            return Err(LoadError::InvalidLength(pid));
+        }
+
+        if prog.start() as usize >= prog.len() {
+            return Err(LoadError::InvalidStart(pid));
+        }
+
+        if !prog.instructions().iter().all(|x| self.standard.is_legal(x)) {
+            return Err(LoadError::IllegalInstruction(pid));
         }
+
+        Ok(())
     }
 
-    /// Store an `Instruction` into the memory location pointed at by the A
-    /// field of the instruction loaded into the instruction register
+    /// Copy a checked program into memory and queue its first process
     ///
     /// # Arguments
-    /// * `instr`: `Instruction` to store
-    fn store_effective_a(&mut self, instr: T)
+    /// * `dest`: address to load the program at, in the core
+    /// * `maybe_pin`: p-space identifier of the warrior
+    /// * `prog`: program to load
+    fn place(
+        &mut self,
+        dest:      Address,
+        maybe_pin: Option<Pin>,
+        prog:      &Warrior<T>,
+        )
+    {
+        let pid = self.pins.len() as Pid;
+        let key = maybe_pin.or_else(|| prog.pin())
+            .map_or(PSpaceKey::Private(pid), PSpaceKey::Pin);
+
+        let cycle_memory_iter = (0..self.size())
+            .cycle()
+            .skip(dest as usize)
+            .take(prog.len())
+            .enumerate();
+
+        // copy program into memory, with its fields in range
+        for (i, j) in cycle_memory_iter {
+            let mut instr = prog.instructions()[i].clone();
+            let (a, b) = (instr.a(), instr.b());
+            instr.set_a(self.normalize(a as i64));
+            instr.set_b(self.normalize(b as i64));
+            self.memory[j] = instr;
+        }
+
+        self.pins.insert(pid, key);
+        self.origins.insert(pid, dest);
+        self.pspace_mut(key);
+
+        let start = (dest + prog.start()) % self.size() as Address;
+
+        let mut q = VecDeque::new();
+        q.push_back(start);
+        self.process_queue.push_back((pid, q));
+    }
+
+    /// Let the Mars run after warriors were loaded
+    ///
+    /// # Arguments
+    /// * `was_empty`: was the process queue empty before the load, in which
+    ///   case the program counter is taken from the first warrior
+    fn start(&mut self, was_empty: bool)
     {
-        let eff_addr = self.effective_addr_a();
-        self.store(eff_addr, instr)
+        if was_empty {
+            let &mut (curr_pid, ref mut curr_q) = self.process_queue
+                .front_mut()
+                .unwrap();
+
+            self.pc = curr_q.pop_front().unwrap();
+            self.pid = curr_pid;
+        }
+
+        self.halted = false;
+    }
+
+    /// Get a p-space, creating it if it does not exist yet. A new p-space
+    /// holds `-1` in location 0, as no round has been played
+    ///
+    /// # Arguments
+    /// * `key`: key of the p-space
+    fn pspace_mut(&mut self, key: PSpaceKey) -> &mut Vec<Value>
+    {
+        let pspace_size = self.pspace_size;
+        self.pspace.entry(key).or_insert_with(|| {
+            let mut pspace = vec![0; pspace_size];
+            if let Some(result) = pspace.first_mut() {
+                *result = -1;
+            }
+            pspace
+        })
+    }
+
+    /// Store a value in a specified pspace
+    ///
+    /// # Arguments
+    /// * `key`: key of the p-space
+    /// * `index`: location in the pspace to store, taken modulo its size
+    /// * `value`: value to store
+    fn store_pspace(&mut self, key: PSpaceKey, index: Value, value: Value)
+    {
+        let pspace = self.pspace_mut(key);
+        if !pspace.is_empty() {
+            let i = (index as i64).rem_euclid(pspace.len() as i64);
+            pspace[i as usize] = value;
+        }
     }
 
     /// Store an `Instruction` into the memory location pointed at by the B
@@ -667,31 +1139,48 @@ where T: traits::Instruction
         self.memory[addr as usize % self.size()].clone()
     }
 
-    /// Fetch an instruction from a programs private storage
+    /// Fetch a value from a programs private storage
     ///
     /// # Arguments
-    /// * `pin`: pin of program, used as lookup key
-    /// * `addr`: address of pspace to access
-    fn fetch_pspace(&self, pin: Pin, addr: Address) -> Value
+    /// * `key`: key of the p-space
+    /// * `index`: location in the pspace to access, taken modulo its size
+    fn fetch_pspace(&mut self, key: PSpaceKey, index: Value) -> Value
     {
-        if let Some(pspace) = self.pspace.get(&pin) {
-            pspace[addr as usize % pspace.len()]
+        let pspace = self.pspace_mut(key);
+        if pspace.is_empty() {
+            0
         } else {
-            // TODO: create new pspace
-            unimplemented!();
+            let i = (index as i64).rem_euclid(pspace.len() as i64);
+            pspace[i as usize]
         }
     }
 
+    /// Get the key of the p-space of the warrior currently executing
+    fn current_pspace(&self) -> PSpaceKey
+    {
+        self.pspace_key(self.pid).unwrap_or(PSpaceKey::Private(self.pid))
+    }
+
     /// Fetch copy of instruction pointed at by the A field of the instruction
-    /// loaded into the instruction register
+    /// loaded into the instruction register, as it was when the operand was
+    /// evaluated
     fn fetch_effective_a(&self) -> T
     {
-        self.fetch(self.effective_addr_a())
+        self.a_reg.clone()
     }
 
     /// Fetch copy of instruction pointed at by the B field of the instruction
-    /// loaded into the instruction register
+    /// loaded into the instruction register, as it was when the operand was
+    /// evaluated
     fn fetch_effective_b(&self) -> T
+    {
+        self.b_reg.clone()
+    }
+
+    /// Fetch copy of the instruction currently in memory where the B field
+    /// of the instruction loaded into the instruction register points. This
+    /// is the instruction results are written into
+    fn fetch_target(&self) -> T
     {
         self.fetch(self.effective_addr_b())
     }
@@ -706,7 +1195,7 @@ where T: traits::Instruction
     #[inline]
     fn exec_dat(&mut self) -> SimulationEvent
     {
-        let _ = self.current_queue_mut().unwrap().pop_front();
+        // the process is not queued again
         SimulationEvent::Terminated
     }
 
@@ -717,7 +1206,7 @@ where T: traits::Instruction
     fn exec_mov(&mut self) -> SimulationEvent
     {
         let a     = self.fetch_effective_a();
-        let mut b = self.fetch_effective_b();
+        let mut b = self.fetch_target();
 
         let (a_a, a_b) = (a.a(), a.b());
 
@@ -749,34 +1238,8 @@ where T: traits::Instruction
     #[inline]
     fn exec_add(&mut self) -> SimulationEvent
     {
-        // TODO: math needs to be done modulo core size
-        let a     = self.fetch_effective_a();
-        let mut b = self.fetch_effective_b();
-
-        let (a_a, a_b) = (a.a(), a.b());
-        let (b_a, b_b) = (b.a(), b.b());
-
-        match self.ir.modifier() {
-            Modifier::A  => { b.set_a((b_a + a_a) % self.size() as Value); }
-            Modifier::B  => { b.set_b((b_b + a_b) % self.size() as Value); }
-            Modifier::BA => { b.set_a((b_a + a_b) % self.size() as Value); }
-            Modifier::AB => { b.set_b((b_b + a_a) % self.size() as Value); }
-            Modifier::F
-                | Modifier::I =>
-            {
-                b.set_a((b_a + a_a) % self.size() as Value);
-                b.set_b((b_b + a_b) % self.size() as Value);
-            }
-            Modifier::X =>
-            {
-                b.set_b((b_b + a_a) % self.size() as Value);
-                b.set_a((b_a + a_b) % self.size() as Value);
-            }
-        }
-
-        self.store_effective_b(b);
-        self.step_and_queue_pc()
-    }
+        self.exec_arithmetic(|b, a| Some(b + a))
+    }
 
     /// Execute `sub` instruction
     ///
@@ -784,33 +1247,7 @@ where T: traits::Instruction
     #[inline]
     fn exec_sub(&mut self) -> SimulationEvent
     {
-        // TODO: math needs to be done modulo core size
-        let a     = self.fetch_effective_a();
-        let mut b = self.fetch_effective_b();
-
-        let (a_a, a_b) = (a.a(), a.b());
-        let (b_a, b_b) = (b.a(), b.b());
-
-        match self.ir.modifier() {
-            Modifier::A  => { b.set_a((b_a - a_a) % self.size() as Value); }
-            Modifier::B  => { b.set_b((b_b - a_b) % self.size() as Value); }
-            Modifier::BA => { b.set_a((b_a - a_b) % self.size() as Value); }
-            Modifier::AB => { b.set_b((b_b - a_a) % self.size() as Value); }
-            Modifier::F
-                | Modifier::I =>
-            {
-                b.set_a((b_a - a_a) % self.size() as Value);
-                b.set_b((b_b - a_b) % self.size() as Value);
-            }
-            Modifier::X =>
-            {
-                b.set_b((b_b - a_a) % self.size() as Value);
-                b.set_a((b_a - a_b) % self.size() as Value);
-            }
-        }
-
-        self.store_effective_b(b);
-        self.step_and_queue_pc()
+        self.exec_arithmetic(|b, a| Some(b - a))
     }
 
     /// Execute `mul` instruction
@@ -819,105 +1256,100 @@ where T: traits::Instruction
     #[inline]
     fn exec_mul(&mut self) -> SimulationEvent
     {
-        // TODO: math needs to be done modulo core size
-        let a     = self.fetch_effective_a();
-        let mut b = self.fetch_effective_b();
-
-        let (a_a, a_b) = (a.a(), a.b());
-        let (b_a, b_b) = (b.a(), b.b());
-
-        match self.ir.modifier() {
-            Modifier::A  => { b.set_a((b_a * a_a) % self.size() as Value); }
-            Modifier::B  => { b.set_b((b_b * a_b) % self.size() as Value); }
-            Modifier::BA => { b.set_a((b_a * a_b) % self.size() as Value); }
-            Modifier::AB => { b.set_b((b_b * a_a) % self.size() as Value); }
-            Modifier::F
-                | Modifier::I =>
-            {
-                b.set_a((b_a * a_a) % self.size() as Value);
-                b.set_b((b_b * a_b) % self.size() as Value);
-            }
-            Modifier::X =>
-            {
-                b.set_b((b_b * a_a) % self.size() as Value);
-                b.set_a((b_a * a_b) % self.size() as Value);
-            }
-        }
-
-        self.store_effective_b(b);
-        self.step_and_queue_pc()
+        self.exec_arithmetic(|b, a| Some(b * a))
     }
 
-    /// Execute `div` instruction
+    /// Execute `div` instruction. Dividing by zero terminates the process,
+    /// though with `F`, `X` or `I` the other field is still divided
     ///
     /// Supported Modifiers: `A` `B` `AB` `BA` `X` `F`
     #[inline]
     fn exec_div(&mut self) -> SimulationEvent
     {
-        // TODO: math needs to be done modulo core size
-        // TODO: division by zero needs to kill the process
-        let a     = self.fetch_effective_a();
-        let mut b = self.fetch_effective_b();
-
-        let (a_a, a_b) = (a.a(), a.b());
-        let (b_a, b_b) = (b.a(), b.b());
-
-        match self.ir.modifier() {
-            Modifier::A  => { b.set_a((b_a / a_a) % self.size() as Value); }
-            Modifier::B  => { b.set_b((b_b / a_b) % self.size() as Value); }
-            Modifier::BA => { b.set_a((b_a / a_b) % self.size() as Value); }
-            Modifier::AB => { b.set_b((b_b / a_a) % self.size() as Value); }
-            Modifier::F
-                | Modifier::I =>
-            {
-                b.set_a((b_a / a_a) % self.size() as Value);
-                b.set_b((b_b / a_b) % self.size() as Value);
-            }
-            Modifier::X =>
-            {
-                b.set_b((b_b / a_a) % self.size() as Value);
-                b.set_a((b_a / a_b) % self.size() as Value);
-            }
-        };
-
-        self.store_effective_b(b);
-        self.step_and_queue_pc()
+        self.exec_arithmetic(i64::checked_div)
     }
 
-    /// Execute `mod` instruction
+    /// Execute `mod` instruction. Dividing by zero terminates the process,
+    /// though with `F`, `X` or `I` the other field is still divided
     ///
     /// Supported Modifiers: `A` `B` `AB` `BA` `X` `F`
     #[inline]
     fn exec_mod(&mut self) -> SimulationEvent
     {
-        // TODO: math needs to be done modulo core size
-        // TODO: division by zero needs to kill the process
+        self.exec_arithmetic(i64::checked_rem)
+    }
+
+    /// Execute an arithmetic instruction, combining the fields of the B
+    /// target with those of the A target selected by the modifier. Results
+    /// are taken modulo the size of memory
+    ///
+    /// # Arguments
+    /// * `op`: operation applied to a B field and an A field, in that order,
+    ///   giving `None` if the process should be terminated
+    fn exec_arithmetic<F>(&mut self, op: F) -> SimulationEvent
+        where F: Fn(i64, i64) -> Option<i64>
+    {
         let a     = self.fetch_effective_a();
-        let mut b = self.fetch_effective_b();
+        let b     = self.fetch_effective_b();
+        let mut t = self.fetch_target();
 
-        let (a_a, a_b) = (a.a(), a.b());
-        let (b_a, b_b) = (b.a(), b.b());
+        let (a_a, a_b) = (a.a() as i64, a.b() as i64);
+        let (b_a, b_b) = (b.a() as i64, b.b() as i64);
 
-        match self.ir.modifier() {
-            Modifier::A  => { b.set_a((b_a % a_a) % self.size() as Value); }
-            Modifier::B  => { b.set_b((b_b % a_b) % self.size() as Value); }
-            Modifier::BA => { b.set_a((b_a % a_b) % self.size() as Value); }
-            Modifier::AB => { b.set_b((b_b % a_a) % self.size() as Value); }
+        let written = match self.ir.modifier() {
+            Modifier::A  => self.write_result(&mut t, true, op(b_a, a_a)),
+            Modifier::B  => self.write_result(&mut t, false, op(b_b, a_b)),
+            Modifier::BA => self.write_result(&mut t, true, op(b_a, a_b)),
+            Modifier::AB => self.write_result(&mut t, false, op(b_b, a_a)),
             Modifier::F
                 | Modifier::I =>
             {
-                b.set_a((b_a % a_a) % self.size() as Value);
-                b.set_b((b_b % a_b) % self.size() as Value);
+                let a_written = self.write_result(&mut t, true, op(b_a, a_a));
+                let b_written = self.write_result(&mut t, false, op(b_b, a_b));
+                a_written && b_written
             }
             Modifier::X =>
             {
-                b.set_b((b_b % a_a) % self.size() as Value);
-                b.set_a((b_a % a_b) % self.size() as Value);
+                let b_written = self.write_result(&mut t, false, op(b_b, a_a));
+                let a_written = self.write_result(&mut t, true, op(b_a, a_b));
+                a_written && b_written
             }
         };
 
-        self.store_effective_b(b);
-        self.step_and_queue_pc()
+        self.store_effective_b(t);
+
+        if written {
+            self.step_and_queue_pc()
+        } else {
+            // the process is not queued again
+            SimulationEvent::Terminated
+        }
+    }
+
+    /// Write the result of an arithmetic operation into a field of an
+    /// instruction, modulo the size of memory
+    ///
+    /// # Arguments
+    /// * `instr`: instruction to write into
+    /// * `use_a_field`: should the A field be written, or B
+    /// * `result`: result of the operation, if it has one
+    ///
+    /// # Return
+    /// `true` if the field was written
+    fn write_result(&self, instr: &mut T, use_a_field: bool,
+        result: Option<i64>) -> bool
+    {
+        let value = match result {
+            Some(value) => self.normalize(value),
+            None        => return false,
+        };
+
+        if use_a_field {
+            instr.set_a(value);
+        } else {
+            instr.set_b(value);
+        }
+        true
     }
 
     /// Execute `jmp` instruction
@@ -926,18 +1358,8 @@ where T: traits::Instruction
     #[inline]
     fn exec_jmp(&mut self) -> SimulationEvent
     {
-        match self.ir.a_mode() {
-            AddressingMode::Immediate
-                | AddressingMode::Direct =>
-            {
-                let offset = self.ir.a();
-                self.jump_and_queue_pc(offset);
-            }
-            // TODO
-            _ => unimplemented!()
-        };
-
-        SimulationEvent::Jumped
+        let target = self.effective_addr_a();
+        self.jump_and_queue_pc(target)
     }
 
     /// Execute `jmz` instruction
@@ -947,7 +1369,7 @@ where T: traits::Instruction
     fn exec_jmz(&mut self) -> SimulationEvent
     {
         let b = self.fetch_effective_b();
-        let offset = self.ir.a(); // TODO: needs to calculate jump offset
+        let target = self.effective_addr_a();
 
         let jump = match self.ir.modifier() {
             Modifier::A
@@ -960,20 +1382,21 @@ where T: traits::Instruction
         };
 
         if jump {
-            self.jump_and_queue_pc(offset)
+            self.jump_and_queue_pc(target)
         } else {
             self.step_and_queue_pc()
         }
     }
 
-    /// Execute `jmn` instruction
+    /// Execute `jmn` instruction. With `F`, `X` or `I` the jump is taken if
+    /// either field is non-zero
     ///
     /// Supported Modifiers: `B`
     #[inline]
     fn exec_jmn(&mut self) -> SimulationEvent
     {
         let b = self.fetch_effective_b();
-        let offset = self.ir.a(); // TODO: needs to calculate jump offset
+        let target = self.effective_addr_a();
 
         let jump = match self.ir.modifier() {
             Modifier::A
@@ -982,11 +1405,11 @@ where T: traits::Instruction
                 | Modifier::AB => b.b() != 0,
             Modifier::F
                 | Modifier::I
-                | Modifier::X => b.a() != 0 && b.b() != 0,
+                | Modifier::X => b.a() != 0 || b.b() != 0,
         };
 
         if jump {
-            self.jump_and_queue_pc(offset)
+            self.jump_and_queue_pc(target)
         } else {
             self.step_and_queue_pc()
         }
@@ -998,24 +1421,35 @@ where T: traits::Instruction
     #[inline]
     fn exec_djn(&mut self) -> SimulationEvent
     {
-        // predecrement the instruction before checking if its not zero
+        // decrement both the target and its copy, which is tested for zero
+        let mut t = self.fetch_target();
         let mut b = self.fetch_effective_b();
-        let (b_a, b_b) = (b.a(), b.b());
 
-        match self.ir.modifier() {
-            Modifier::A
-                | Modifier::BA => { b.set_a(b_a - 1); },
-            Modifier::B
-                | Modifier::AB => { b.set_b(b_b - 1); },
-            Modifier::F
-                | Modifier::I
-                | Modifier::X =>
-            {
-                b.set_a(b_a - 1);
-                b.set_b(b_b - 1);
-            }
+        for instr in [&mut t, &mut b].iter_mut() {
+            let (i_a, i_b) = (instr.a() as i64, instr.b() as i64);
+
+            match self.ir.modifier() {
+                Modifier::A
+                    | Modifier::BA => { instr.set_a(self.normalize(i_a - 1)); },
+                Modifier::B
+                    | Modifier::AB => { instr.set_b(self.normalize(i_b - 1)); },
+                Modifier::F
+                    | Modifier::I
+                    | Modifier::X =>
+                {
+                    instr.set_a(self.normalize(i_a - 1));
+                    instr.set_b(self.normalize(i_b - 1));
+                }
+            };
+        }
+
+        // earlier standards test the decremented instruction in memory
+        self.b_reg = if self.standard.copies_operands() {
+            b
+        } else {
+            t.clone()
         };
-        self.store_effective_b(b);
+        self.store_effective_b(t);
 
         self.exec_jmn()
     }
@@ -1026,11 +1460,17 @@ where T: traits::Instruction
     #[inline]
     fn exec_spl(&mut self) -> SimulationEvent
     {
-        if self.process_count() < self.max_processes(){
+        if self.processes_of(self.pid) < self.max_processes {
             let target = self.effective_addr_a();
 
-            self.current_queue_mut().unwrap().push_back(target);
-            self.step_and_queue_pc();
+            if self.standard.splits_target_first() {
+                self.current_queue_mut().unwrap().push_back(target);
+                self.step_and_queue_pc();
+            } else {
+                // the next instruction is queued before the new process
+                self.step_and_queue_pc();
+                self.current_queue_mut().unwrap().push_back(target);
+            }
             SimulationEvent::Split
         } else {
             self.step_and_queue_pc()
@@ -1046,19 +1486,11 @@ where T: traits::Instruction
         let a = self.fetch_effective_a();
         let b = self.fetch_effective_b();
 
-        let skip = match self.ir.modifier() {
-            Modifier::A       => a.a() == b.b(),
-            Modifier::B       => a.b() == b.b(),
-            Modifier::BA      => a.a() == b.b(),
-            Modifier::AB      => a.b() == b.a(),
-            Modifier::X       => a.b() == b.a() &&
-                                 a.a() == b.b(),
-            Modifier::F
-                | Modifier::I => a.a() == b.a() &&
-                                 a.b() == b.b(),
-        };
-
-        if skip { self.skip_and_queue_pc() } else { self.step_and_queue_pc() }
+        if self.equal(&a, &b) {
+            self.skip_and_queue_pc()
+        } else {
+            self.step_and_queue_pc()
+        }
     }
 
     /// Execute `sne` instruction
@@ -1070,19 +1502,11 @@ where T: traits::Instruction
         let a = self.fetch_effective_a();
         let b = self.fetch_effective_b();
 
-        let skip = match self.ir.modifier() {
-            Modifier::A       => a.a() != b.b(),
-            Modifier::B       => a.b() != b.b(),
-            Modifier::BA      => a.a() != b.b(),
-            Modifier::AB      => a.b() != b.a(),
-            Modifier::X       => a.b() != b.a() &&
-                                 a.a() != b.b(),
-            Modifier::F
-                | Modifier::I => a.a() != b.a() &&
-                                 a.b() != b.b(),
-        };
-
-        if skip { self.skip_and_queue_pc() } else { self.step_and_queue_pc() }
+        if self.equal(&a, &b) {
+            self.step_and_queue_pc()
+        } else {
+            self.skip_and_queue_pc()
+        }
     }
 
     /// Execute `slt` instruction
@@ -1091,44 +1515,118 @@ where T: traits::Instruction
     #[inline]
     fn exec_slt(&mut self) -> SimulationEvent
     {
-        let a = self.fetch_effective_a();
-        let b = self.fetch_effective_b();
-
-        let skip = match self.ir.modifier() {
-            Modifier::A       => a.a() < b.b(),
-            Modifier::B       => a.b() < b.b(),
-            Modifier::BA      => a.a() < b.b(),
-            Modifier::AB      => a.b() < b.a(),
-            Modifier::X       => a.b() < b.a() &&
-                                 a.a() < b.b(),
-            Modifier::F
-                | Modifier::I => a.a() < b.a() &&
-                                 a.b() < b.b(),
+        let (a, b) = if self.standard.copies_operands() {
+            (self.fetch_effective_a(), self.fetch_effective_b())
+        } else {
+            (self.fetch(self.effective_addr_a()), self.fetch_target())
         };
 
-        if skip { self.skip_and_queue_pc() } else { self.step_and_queue_pc() }
+        if self.compare_fields(&a, &b, |x, y| x < y) {
+            self.skip_and_queue_pc()
+        } else {
+            self.step_and_queue_pc()
+        }
     }
 
-    /// Execute `ldp` instruction
+    /// Are two instructions equal in the parts selected by the modifier of
+    /// the instruction register. `I` compares whole instructions
     ///
-    /// Supported Modifiers: `A` `B` `AB` `BA` `X` `F` `I`
+    /// # Arguments
+    /// * `a`: A operand
+    /// * `b`: B operand
+    fn equal(&self, a: &T, b: &T) -> bool
+    {
+        let same_instruction = self.ir.modifier() != Modifier::I
+            || (a.op() == b.op()
+                && a.modifier() == b.modifier()
+                && a.a_mode() == b.a_mode()
+                && a.b_mode() == b.b_mode());
+
+        same_instruction && self.compare_fields(a, b, |x, y| x == y)
+    }
+
+    /// Compare the fields of two instructions selected by the modifier of the
+    /// instruction register
+    ///
+    /// # Arguments
+    /// * `a`: A operand
+    /// * `b`: B operand
+    /// * `cmp`: comparison every pair of fields has to pass
+    fn compare_fields<F>(&self, a: &T, b: &T, cmp: F) -> bool
+        where F: Fn(Value, Value) -> bool
+    {
+        match self.ir.modifier() {
+            Modifier::A       => cmp(a.a(), b.a()),
+            Modifier::B       => cmp(a.b(), b.b()),
+            Modifier::AB      => cmp(a.a(), b.b()),
+            Modifier::BA      => cmp(a.b(), b.a()),
+            Modifier::X       => cmp(a.a(), b.b()) && cmp(a.b(), b.a()),
+            Modifier::F
+                | Modifier::I => cmp(a.a(), b.a()) && cmp(a.b(), b.b()),
+        }
+    }
+
+    /// Execute `ldp` instruction, loading the p-space location named by the
+    /// A operand into the B target
+    ///
+    /// Supported Modifiers: `A` `B` `AB` `BA` `X` `F` `I`, where `X` `F` and
+    /// `I` behave as `B`
     #[inline]
     fn exec_ldp(&mut self) -> SimulationEvent
     {
+        let a     = self.fetch_effective_a();
+        let mut b = self.fetch_target();
+        let key   = self.current_pspace();
+
+        let index = match self.ir.modifier() {
+            Modifier::A
+                | Modifier::AB => a.a(),
+            Modifier::BA
+                | Modifier::B
+                | Modifier::X
+                | Modifier::F
+                | Modifier::I => a.b(),
+        };
+        let value = self.fetch_pspace(key, index) as i64;
+        let value = self.normalize(value);
+
         match self.ir.modifier() {
-            _ => unimplemented!()
+            Modifier::A
+                | Modifier::BA => { b.set_a(value); }
+            Modifier::AB
+                | Modifier::B
+                | Modifier::X
+                | Modifier::F
+                | Modifier::I => { b.set_b(value); }
         }
+
+        self.store_effective_b(b);
+        self.step_and_queue_pc()
     }
 
-    /// Execute `stp` instruction
+    /// Execute `stp` instruction, storing the A value in the p-space location
+    /// named by the B operand
     ///
-    /// Supported Modifiers: `A` `B` `AB` `BA` `X` `F` `I`
+    /// Supported Modifiers: `A` `B` `AB` `BA` `X` `F` `I`, where `X` `F` and
+    /// `I` behave as `B`
     #[inline]
     fn exec_stp(&mut self) -> SimulationEvent
     {
+        let a   = self.fetch_effective_a();
+        let b   = self.fetch_effective_b();
+        let key = self.current_pspace();
+
         match self.ir.modifier() {
-            _ => unimplemented!()
+            Modifier::A  => self.store_pspace(key, b.a(), a.a()),
+            Modifier::AB => self.store_pspace(key, b.b(), a.a()),
+            Modifier::BA => self.store_pspace(key, b.a(), a.b()),
+            Modifier::B
+                | Modifier::X
+                | Modifier::F
+                | Modifier::I => self.store_pspace(key, b.b(), a.b()),
         }
+
+        self.step_and_queue_pc()
     }
 
     /// Execute 'nop' instruction
@@ -1145,6 +1643,7 @@ mod test
     use simulation::MarsBuilder;
     use redcode::traits::Instruction;
     use redcode::Instruction as InstructionStruct;
+    use redcode::test_utils::{instr, looper};
     use super::*;
 
     fn mov_test_program(modifier: Modifier) -> Vec<InstructionStruct>
@@ -1177,6 +1676,17 @@ mod test
         ]
     }
 
+    /// Load a program at address 0 and execute its first instruction
+    fn step_once(prog: Vec<InstructionStruct>) -> Mars<InstructionStruct>
+    {
+        let mut mars: Mars<InstructionStruct> = MarsBuilder::new()
+            .build_and_load(vec![(0, None, &Warrior::from(prog))])
+            .unwrap();
+
+        let _ = mars.step();
+        mars
+    }
+
     #[test]
     fn test_load_batch_fails_empty_vector()
     {
@@ -1188,14 +1698,13 @@ mod test
     }
 
     #[test]
-    #[ignore]
     fn test_load_batch_load_fails_invalid_distance()
     {
         let mut mars: Mars<InstructionStruct> = MarsBuilder::new()
             .min_distance(10)
             .build();
 
-        let useless_program = vec![Default::default(); 1];
+        let useless_program = Warrior::from(vec![Default::default(); 1]);
 
         // intentionally load the programs with invalid spacings
         let result = mars.load_batch(vec![
@@ -1203,151 +1712,460 @@ mod test
             (1, None, &useless_program),
         ]);
         
-        assert_eq!(Err(LoadError::InvalidDistance), result);
+        assert_eq!(Err(LoadError::InvalidDistance(0, 1)), result);
     }
 
     #[test]
-    fn test_batch_load_succeeds()
+    fn test_load_batch_checks_distance_around_the_core()
     {
         let mut mars: Mars<InstructionStruct> = MarsBuilder::new()
+            .size(100)
             .min_distance(10)
             .max_length(10)
             .build();
 
-        let useless_program = vec![Default::default(); 10];
+        let useless_program = Warrior::from(vec![Default::default(); 1]);
 
-        // intentionally load the programs with invalid spacings
+        // 95 is only 5 away from 0 going around the end of the core
         let result = mars.load_batch(vec![
             (0, None, &useless_program),
-            (21, None, &useless_program),
+            (50, None, &useless_program),
+            (95, None, &useless_program),
         ]);
-        
-        assert_eq!(Ok(()), result);
+
+        assert_eq!(Err(LoadError::InvalidDistance(0, 2)), result);
     }
 
     #[test]
-    fn test_step_errors_when_halted()
+    fn test_load_batch_fails_invalid_length()
     {
-        let mut mars: Mars<InstructionStruct> = MarsBuilder::new().build();
-        let result = mars.step();
+        let mut mars: Mars<InstructionStruct> = MarsBuilder::new()
+            .max_length(10)
+            .build();
 
-        assert_eq!(Err(SimulationError::Halted), result);
+        let short = Warrior::from(vec![Default::default(); 10]);
+        let long = Warrior::from(vec![Default::default(); 11]);
+        let empty = Warrior::from(vec![]);
+
+        assert_eq!(
+            Err(LoadError::InvalidLength(1)),
+            mars.load_batch(vec![(0, None, &short), (4000, None, &long)])
+            );
+        assert_eq!(
+            Err(LoadError::InvalidLength(0)),
+            mars.load_batch(vec![(0, None, &empty)])
+            );
     }
 
     #[test]
-    pub fn test_dat()
+    fn test_load_batch_folds_destinations()
     {
-        let mut mars: Mars<InstructionStruct> = MarsBuilder::new().build_and_load(vec![
-            (0, None, &vec![Default::default(); 1])
-            ])
+        let mut prog = Warrior::from(vec![Default::default(); 4]);
+        prog.set_start(3);
+
+        let mars: Mars<InstructionStruct> = MarsBuilder::new()
+            .build_and_load(vec![(8100, None, &prog)])
             .unwrap();
 
-        let result = mars.step();
-        assert_eq!(Ok(SimulationEvent::Halted), result);
-        assert_eq!(true, mars.halted());
+        assert_eq!(103, mars.pc());
+
+        // 8050 is folded to 50, too close to the warrior at 0
+        let mut mars: Mars<InstructionStruct> = MarsBuilder::new().build();
+        assert_eq!(
+            Err(LoadError::InvalidDistance(0, 1)),
+            mars.load_batch(vec![(0, None, &prog), (8050, None, &prog)])
+            );
     }
 
     #[test]
-    fn test_mov_i_mode()
+    fn test_batch_load_succeeds()
     {
-        let prog = mov_test_program(Modifier::I);
-
         let mut mars: Mars<InstructionStruct> = MarsBuilder::new()
-            .build_and_load(vec![
-                (0, None, &prog)
-            ])
-            .unwrap();
+            .min_distance(10)
+            .max_length(10)
+            .build();
 
-        let init_pc    = mars.pc();
+        let useless_program = Warrior::from(vec![Default::default(); 10]);
 
-        assert_eq!(Ok(SimulationEvent::Stepped), mars.step());
-        assert_eq!(prog[1],                      mars.memory()[2]);
-        assert_eq!(init_pc + 1,                  mars.pc());
+        // intentionally load the programs with invalid spacings
+        let result = mars.load_batch(vec![
+            (0, None, &useless_program),
+            (21, None, &useless_program),
+        ]);
+        
+        assert_eq!(Ok(()), result);
     }
 
     #[test]
-    fn test_mov_a_mode()
+    fn test_load_checks_loaded_warriors()
     {
-        let prog = mov_test_program(Modifier::A);
-
-        let mut mars: Mars<InstructionStruct> = MarsBuilder::new()
-            .build_and_load(vec![
-                (0, None, &prog)
-            ])
-            .unwrap();
+        let looper = looper();
+        let mut mars: Mars<InstructionStruct> = MarsBuilder::new().build();
 
-        let init_pc    = mars.pc();
+        assert_eq!(Ok(0), mars.load(0, None, &looper));
+        assert_eq!(Ok(1), mars.load(4000, None, &looper));
+        assert_eq!(
+            Err(LoadError::InvalidDistance(1, 2)),
+            mars.load(4050, None, &looper)
+            );
+        assert_eq!(
+            Err(LoadError::InvalidDistance(0, 3)),
+            mars.load_batch(vec![(2000, None, &looper), (7950, None, &looper)])
+            );
 
-        assert_eq!(Ok(SimulationEvent::Stepped), mars.step());
-        assert_eq!(prog[1].a(),                  mars.memory()[2].a());
-        assert_eq!(prog[1].a_mode(),             mars.memory()[2].a_mode());
-        assert_eq!(init_pc + 1,                  mars.pc());
+        assert_eq!(vec![0, 1], mars.alive_warriors());
+        assert_eq!(0, mars.pc());
+        assert!(!mars.halted());
     }
 
     #[test]
-    fn test_mov_b_mode()
+    fn test_load_while_running()
     {
-        let prog = mov_test_program(Modifier::B);
-
+        let looper = looper();
         let mut mars: Mars<InstructionStruct> = MarsBuilder::new()
-            .build_and_load(vec![
-                (0, None, &prog)
-            ])
+            .build_and_load(vec![(0, None, &looper)])
             .unwrap();
 
-        let init_pc    = mars.pc();
+        let _ = mars.step();
+        assert_eq!(Ok(1), mars.load(100, None, &looper));
 
-        assert_eq!(Ok(SimulationEvent::Stepped), mars.step());
-        assert_eq!(prog[1].b(),                  mars.memory()[2].b());
-        assert_eq!(prog[1].b_mode(),             mars.memory()[2].b_mode());
-        assert_eq!(init_pc + 1,                  mars.pc());
+        // the new warrior runs after the one already loaded
+        assert_eq!(vec![0, 1], mars.alive_warriors());
+        assert_eq!(Ok(SimulationEvent::Jumped), mars.step());
+        assert_eq!(1, mars.pid());
+        assert_eq!(100, mars.pc());
     }
 
     #[test]
-    fn test_mov_ab_mode()
+    fn test_unload()
     {
-        let prog = mov_test_program(Modifier::AB);
-
+        let looper = looper();
         let mut mars: Mars<InstructionStruct> = MarsBuilder::new()
             .build_and_load(vec![
-                (0, None, &prog)
+                (0, None, &looper),
+                (100, None, &looper),
+                (200, None, &looper),
             ])
             .unwrap();
+        mars.memory[1] = looper.instructions()[0];
 
-        let init_pc    = mars.pc();
+        // unloading the running warrior moves on to the next one
+        assert!(mars.unload(0));
+        assert!(!mars.unload(0));
+        assert_eq!((1, 100), (mars.pid(), mars.pc()));
+        assert_eq!(Some(0), mars.death_cycle(0));
+        assert_eq!(looper.instructions()[0], mars.memory()[1]);
+        assert!(!mars.halted());
 
-        assert_eq!(Ok(SimulationEvent::Stepped), mars.step());
-        assert_eq!(prog[1].b(),                  mars.memory()[2].a());
-        assert_eq!(prog[1].b_mode(),             mars.memory()[2].a_mode());
-        assert_eq!(init_pc + 1,                  mars.pc());
+        // its place can be taken by another warrior
+        assert_eq!(Ok(3), mars.load(0, None, &looper));
+
+        assert!(mars.unload(1));
+        assert!(mars.unload(3));
+        assert!(mars.halted());
+        assert_eq!(Some(MatchOutcome::Win(2)), mars.outcome());
     }
 
     #[test]
-    fn test_mov_ba_mode()
+    fn test_load_batch_starts_at_warrior_start()
     {
-        let prog = mov_test_program(Modifier::BA);
+        let mut prog = Warrior::from(vec![Default::default(); 4]);
+        prog.set_start(3);
 
-        let mut mars: Mars<InstructionStruct> = MarsBuilder::new()
-            .build_and_load(vec![
-                (0, None, &prog)
-            ])
+        let mars: Mars<InstructionStruct> = MarsBuilder::new()
+            .build_and_load(vec![(100, None, &prog)])
             .unwrap();
 
-        let init_pc    = mars.pc();
-
-        assert_eq!(Ok(SimulationEvent::Stepped), mars.step());
-        assert_eq!(prog[1].a(),                  mars.memory()[2].b());
-        assert_eq!(prog[1].a_mode(),             mars.memory()[2].b_mode());
-        assert_eq!(init_pc + 1,                  mars.pc());
+        assert_eq!(103, mars.pc());
     }
 
     #[test]
-    fn test_mov_x_mode()
+    fn test_load_batch_uses_warrior_pin()
     {
-        let prog = mov_test_program(Modifier::X);
+        let mut prog = Warrior::from(vec![Default::default(); 1]);
+        prog.set_pin(Some(42));
+
+        let mars: Mars<InstructionStruct> = MarsBuilder::new()
+            .build_and_load(vec![(0, None, &prog), (100, Some(7), &prog)])
+            .unwrap();
+
+        assert!(mars.pspace().contains_key(&PSpaceKey::Pin(42)));
+        assert!(mars.pspace().contains_key(&PSpaceKey::Pin(7)));
+    }
+
+    #[test]
+    fn test_step_errors_when_halted()
+    {
+        let mut mars: Mars<InstructionStruct> = MarsBuilder::new().build();
+        let result = mars.step();
+
+        assert_eq!(Err(SimulationError::Halted), result);
+    }
+
+    #[test]
+    pub fn test_dat()
+    {
+        let mut mars: Mars<InstructionStruct> = MarsBuilder::new().build_and_load(vec![
+            (0, None, &Warrior::from(vec![Default::default(); 1]))
+            ])
+            .unwrap();
+
+        let result = mars.step();
+        assert_eq!(Ok(SimulationEvent::Halted { pid: 0, cycle: 0 }), result);
+        assert!(mars.halted());
+    }
+
+    #[test]
+    fn test_last_warrior_running_wins()
+    {
+        let dat = Warrior::from(vec![Default::default(); 1]);
+        let looper = looper();
+
+        let mut mars: Mars<InstructionStruct> = MarsBuilder::new()
+            .build_and_load(vec![(0, None, &looper), (100, None, &dat)])
+            .unwrap();
+
+        assert_eq!(None, mars.outcome());
+
+        while !mars.halted() {
+            let _ = mars.step();
+        }
+
+        assert_eq!(Some(MatchOutcome::Win(0)), mars.outcome());
+        assert_eq!(Some(1), mars.death_cycle(1));
+        assert_eq!(None, mars.death_cycle(0));
+    }
+
+    #[test]
+    fn test_step_reports_winner()
+    {
+        let dat = Warrior::from(vec![Default::default(); 1]);
+        let looper = looper();
+
+        let mut mars: Mars<InstructionStruct> = MarsBuilder::new()
+            .build_and_load(vec![(0, None, &dat), (100, None, &looper)])
+            .unwrap();
+
+        // warriors run in the order they were loaded
+        assert_eq!(
+            Ok(SimulationEvent::Won { winner: 1, died: 0, cycle: 0 }),
+            mars.step()
+            );
+        assert!(mars.halted());
+        assert_eq!(Some(0), mars.death_cycle(0));
+    }
+
+    #[test]
+    fn test_step_reports_warrior_deaths()
+    {
+        let dat = Warrior::from(vec![Default::default(); 1]);
+        let looper = looper();
+
+        let mut mars: Mars<InstructionStruct> = MarsBuilder::new()
+            .build_and_load(vec![
+                (0, None, &looper),
+                (100, None, &looper),
+                (200, None, &dat),
+            ])
+            .unwrap();
+
+        assert_eq!(vec![0, 1, 2], mars.alive_warriors());
+
+        assert_eq!(Ok(SimulationEvent::Jumped), mars.step());
+        assert_eq!(Ok(SimulationEvent::Jumped), mars.step());
+        assert_eq!(
+            Ok(SimulationEvent::WarriorDied { pid: 2, cycle: 2 }),
+            mars.step()
+            );
+
+        assert_eq!(vec![0, 1], mars.alive_warriors());
+        assert_eq!(0, mars.processes_of(2));
+        assert!(!mars.halted());
+    }
+
+    #[test]
+    fn test_death_ending_simulation_is_reported()
+    {
+        let dat = Warrior::from(vec![Default::default(); 1]);
+        let looper = looper();
+
+        let mut mars: Mars<InstructionStruct> = MarsBuilder::new()
+            .build_and_load(vec![
+                (0, None, &looper),
+                (100, None, &dat),
+                (200, None, &dat),
+            ])
+            .unwrap();
+
+        assert_eq!(Ok(SimulationEvent::Jumped), mars.step());
+        assert_eq!(
+            Ok(SimulationEvent::WarriorDied { pid: 1, cycle: 1 }),
+            mars.step()
+            );
+
+        // the last death is reported along with the win
+        assert_eq!(
+            Ok(SimulationEvent::Won { winner: 0, died: 2, cycle: 2 }),
+            mars.step()
+            );
+        assert_eq!(Some(2), mars.death_cycle(2));
+        assert_eq!(vec![0], mars.alive_warriors());
+    }
+
+    #[test]
+    fn test_processes_of()
+    {
+        let splitter = Warrior::from(vec![
+            InstructionStruct::new(
+                OpCode::Spl,
+                Modifier::B,
+                0,
+                AddressingMode::Direct,
+                0,
+                AddressingMode::Direct
+                ),
+        ]);
+        let looper = looper();
+
+        let mut mars: Mars<InstructionStruct> = MarsBuilder::new()
+            .build_and_load(vec![(0, None, &splitter), (100, None, &looper)])
+            .unwrap();
+
+        assert_eq!((1, 1), (mars.processes_of(0), mars.processes_of(1)));
+
+        assert_eq!(Ok(SimulationEvent::Split), mars.step());
+        assert_eq!(Ok(SimulationEvent::Jumped), mars.step());
+        assert_eq!((2, 1), (mars.processes_of(0), mars.processes_of(1)));
+    }
+
+    #[test]
+    fn test_outcome_tie_and_all_dead()
+    {
+        let dat = Warrior::from(vec![Default::default(); 1]);
+        let looper = looper();
+
+        let mut mars: Mars<InstructionStruct> = MarsBuilder::new()
+            .max_cycles(10)
+            .build_and_load(vec![(0, None, &looper), (100, None, &looper)])
+            .unwrap();
+
+        while !mars.halted() {
+            let _ = mars.step();
+        }
+        assert_eq!(Some(MatchOutcome::Tie), mars.outcome());
+
+        let mut mars: Mars<InstructionStruct> = MarsBuilder::new()
+            .build_and_load(vec![(0, None, &dat)])
+            .unwrap();
+
+        assert_eq!(
+            Ok(SimulationEvent::Halted { pid: 0, cycle: 0 }),
+            mars.step()
+            );
+        assert_eq!(Some(MatchOutcome::AllDead), mars.outcome());
+    }
+
+    #[test]
+    fn test_mov_i_mode()
+    {
+        let prog = mov_test_program(Modifier::I);
+
+        let mut mars: Mars<InstructionStruct> = MarsBuilder::new()
+            .build_and_load(vec![
+                (0, None, &Warrior::from(prog.clone()))
+            ])
+            .unwrap();
+
+        let init_pc    = mars.pc();
+
+        assert_eq!(Ok(SimulationEvent::Stepped), mars.step());
+        assert_eq!(prog[1],                      mars.memory()[2]);
+        assert_eq!(init_pc + 1,                  mars.pc());
+    }
+
+    #[test]
+    fn test_mov_a_mode()
+    {
+        let prog = mov_test_program(Modifier::A);
+
+        let mut mars: Mars<InstructionStruct> = MarsBuilder::new()
+            .build_and_load(vec![
+                (0, None, &Warrior::from(prog.clone()))
+            ])
+            .unwrap();
+
+        let init_pc    = mars.pc();
+
+        assert_eq!(Ok(SimulationEvent::Stepped), mars.step());
+        assert_eq!(prog[1].a(),                  mars.memory()[2].a());
+        assert_eq!(prog[1].a_mode(),             mars.memory()[2].a_mode());
+        assert_eq!(init_pc + 1,                  mars.pc());
+    }
+
+    #[test]
+    fn test_mov_b_mode()
+    {
+        let prog = mov_test_program(Modifier::B);
+
+        let mut mars: Mars<InstructionStruct> = MarsBuilder::new()
+            .build_and_load(vec![
+                (0, None, &Warrior::from(prog.clone()))
+            ])
+            .unwrap();
+
+        let init_pc    = mars.pc();
+
+        assert_eq!(Ok(SimulationEvent::Stepped), mars.step());
+        assert_eq!(prog[1].b(),                  mars.memory()[2].b());
+        assert_eq!(prog[1].b_mode(),             mars.memory()[2].b_mode());
+        assert_eq!(init_pc + 1,                  mars.pc());
+    }
+
+    #[test]
+    fn test_mov_ab_mode()
+    {
+        let prog = mov_test_program(Modifier::AB);
+
+        let mut mars: Mars<InstructionStruct> = MarsBuilder::new()
+            .build_and_load(vec![
+                (0, None, &Warrior::from(prog.clone()))
+            ])
+            .unwrap();
+
+        let init_pc    = mars.pc();
+
+        assert_eq!(Ok(SimulationEvent::Stepped), mars.step());
+        assert_eq!(prog[1].b(),                  mars.memory()[2].a());
+        assert_eq!(prog[1].b_mode(),             mars.memory()[2].a_mode());
+        assert_eq!(init_pc + 1,                  mars.pc());
+    }
+
+    #[test]
+    fn test_mov_ba_mode()
+    {
+        let prog = mov_test_program(Modifier::BA);
+
+        let mut mars: Mars<InstructionStruct> = MarsBuilder::new()
+            .build_and_load(vec![
+                (0, None, &Warrior::from(prog.clone()))
+            ])
+            .unwrap();
+
+        let init_pc    = mars.pc();
+
+        assert_eq!(Ok(SimulationEvent::Stepped), mars.step());
+        assert_eq!(prog[1].a(),                  mars.memory()[2].b());
+        assert_eq!(prog[1].a_mode(),             mars.memory()[2].b_mode());
+        assert_eq!(init_pc + 1,                  mars.pc());
+    }
+
+    #[test]
+    fn test_mov_x_mode()
+    {
+        let prog = mov_test_program(Modifier::X);
         let mut mars: Mars<InstructionStruct> = MarsBuilder::new()
             .build_and_load(vec![
-                (0, None, &prog)
+                (0, None, &Warrior::from(prog.clone()))
             ])
             .unwrap();
 
@@ -1368,7 +2186,7 @@ mod test
 
         let mut mars: Mars<InstructionStruct> = MarsBuilder::new()
             .build_and_load(vec![
-                (0, None, &prog)
+                (0, None, &Warrior::from(prog.clone()))
             ])
             .unwrap();
 
@@ -1422,7 +2240,7 @@ mod test
 
         let mut mars: Mars<InstructionStruct> = MarsBuilder::new()
             .max_processes(10)
-            .build_and_load(vec![(0, None, &prog)])
+            .build_and_load(vec![(0, None, &Warrior::from(prog.clone()))])
             .unwrap();
 
         let init_pc = mars.pc();
@@ -1434,50 +2252,794 @@ mod test
         assert_eq!(init_pc + 3, mars.pc());
     }
 
+    /// Does a comparison skip when its A operand points at a `DAT` holding
+    /// `a` and its B operand at one holding `b`
+    fn skips(op: OpCode, modifier: Modifier, a: (Value, Value),
+        b: (Value, Value)) -> bool
+    {
+        use self::AddressingMode::*;
+
+        let mars = step_once(vec![
+            instr(op, modifier, Direct, 1, Direct, 2),
+            instr(OpCode::Dat, Modifier::F, Direct, a.0, Direct, a.1),
+            instr(OpCode::Dat, Modifier::F, Direct, b.0, Direct, b.1),
+        ]);
+        mars.pc() == 2
+    }
+
     #[test]
-    fn test_spl_cant_create_more_than_max_processes()
+    fn test_seq_modifiers()
+    {
+        let seq = |m, a, b| skips(OpCode::Seq, m, a, b);
+
+        assert!(seq(Modifier::A, (5, 1), (5, 9)));
+        assert!(!seq(Modifier::A, (5, 1), (1, 5)));
+        assert!(seq(Modifier::B, (1, 5), (9, 5)));
+        assert!(!seq(Modifier::B, (5, 1), (5, 9)));
+        assert!(seq(Modifier::AB, (5, 1), (9, 5)));
+        assert!(!seq(Modifier::AB, (1, 5), (5, 9)));
+        assert!(seq(Modifier::BA, (1, 5), (5, 9)));
+        assert!(!seq(Modifier::BA, (5, 1), (9, 5)));
+        assert!(seq(Modifier::F, (1, 2), (1, 2)));
+        assert!(!seq(Modifier::F, (1, 2), (1, 3)));
+        assert!(seq(Modifier::X, (1, 2), (2, 1)));
+        assert!(!seq(Modifier::X, (1, 2), (1, 2)));
+        assert!(seq(Modifier::I, (1, 2), (1, 2)));
+        assert!(!seq(Modifier::I, (1, 2), (2, 1)));
+    }
+
+    #[test]
+    fn test_seq_i_compares_whole_instructions()
     {
-        // splitter program, infinitely creates imps
-        let prog = vec![
-            InstructionStruct::new(
-                OpCode::Spl,
-                Modifier::I,
-                2,
-                AddressingMode::Direct,
-                1,
-                AddressingMode::Direct,
-                ),
-            InstructionStruct::new(
-                OpCode::Jmp,
-                Modifier::I,
-                -1,
-                AddressingMode::Direct,
-                1,
-                AddressingMode::Direct,
-                ),
-            InstructionStruct::new(
-                OpCode::Mov,
-                Modifier::I,
-                0,
-                AddressingMode::Direct,
-                1,
-                AddressingMode::Direct,
-            ),
-        ];
+        use self::AddressingMode::*;
 
-        let mut mars: Mars<InstructionStruct> = MarsBuilder::new()
-            .max_processes(10)
-            .build_and_load(vec![(0, None, &prog)])
-            .unwrap();
+        let mars = step_once(vec![
+            instr(OpCode::Seq, Modifier::I, Direct, 1, Direct, 2),
+            instr(OpCode::Dat, Modifier::F, Direct, 1, Direct, 2),
+            instr(OpCode::Dat, Modifier::F, Immediate, 1, Direct, 2),
+        ]);
+        assert_eq!(1, mars.pc());
 
-        assert_eq!(Ok(SimulationEvent::Split), mars.step());
+        let mars = step_once(vec![
+            instr(OpCode::Sne, Modifier::I, Direct, 1, Direct, 2),
+            instr(OpCode::Dat, Modifier::F, Direct, 1, Direct, 2),
+            instr(OpCode::Mov, Modifier::F, Direct, 1, Direct, 2),
+        ]);
+        assert_eq!(2, mars.pc());
+    }
 
-        // run the simulation until it halts because cycles have been exauste
-        while !mars.halted() {
-            let _ = mars.step();
-        }
-        
-        assert_eq!(10, mars.process_count());
+    #[test]
+    fn test_sne_modifiers()
+    {
+        let sne = |m, a, b| skips(OpCode::Sne, m, a, b);
+
+        assert!(sne(Modifier::A, (5, 1), (4, 1)));
+        assert!(!sne(Modifier::A, (5, 1), (5, 9)));
+        assert!(sne(Modifier::B, (1, 5), (1, 4)));
+        assert!(!sne(Modifier::B, (1, 5), (9, 5)));
+        assert!(sne(Modifier::AB, (5, 1), (5, 1)));
+        assert!(!sne(Modifier::AB, (5, 1), (9, 5)));
+        assert!(sne(Modifier::BA, (1, 5), (1, 5)));
+        assert!(!sne(Modifier::BA, (1, 5), (5, 9)));
+        assert!(sne(Modifier::F, (1, 2), (1, 3)));
+        assert!(!sne(Modifier::F, (1, 2), (1, 2)));
+        assert!(sne(Modifier::X, (1, 2), (2, 3)));
+        assert!(!sne(Modifier::X, (1, 2), (2, 1)));
+        assert!(sne(Modifier::I, (1, 2), (3, 2)));
+        assert!(!sne(Modifier::I, (1, 2), (1, 2)));
+    }
+
+    #[test]
+    fn test_slt_modifiers()
+    {
+        let slt = |m, a, b| skips(OpCode::Slt, m, a, b);
+
+        assert!(slt(Modifier::A, (3, 9), (4, 0)));
+        assert!(!slt(Modifier::A, (5, 1), (5, 9)));
+        assert!(slt(Modifier::B, (9, 3), (0, 4)));
+        assert!(!slt(Modifier::B, (1, 5), (9, 5)));
+        assert!(slt(Modifier::AB, (3, 9), (0, 4)));
+        assert!(!slt(Modifier::AB, (3, 9), (4, 0)));
+        assert!(slt(Modifier::BA, (9, 3), (4, 0)));
+        assert!(!slt(Modifier::BA, (9, 3), (0, 4)));
+        assert!(slt(Modifier::F, (1, 2), (2, 3)));
+        assert!(!slt(Modifier::F, (1, 2), (2, 2)));
+        assert!(slt(Modifier::X, (1, 2), (3, 2)));
+        assert!(!slt(Modifier::X, (1, 2), (2, 3)));
+        assert!(slt(Modifier::I, (1, 2), (2, 3)));
+        assert!(!slt(Modifier::I, (2, 2), (2, 3)));
+    }
+
+    #[test]
+    fn test_eval_immediate_points_at_instruction()
+    {
+        use self::AddressingMode::*;
+
+        let mars = step_once(vec![
+            instr(OpCode::Mov, Modifier::I, Immediate, 0, Direct, 1),
+        ]);
+
+        assert_eq!(mars.memory()[0], mars.memory()[1]);
+    }
+
+    #[test]
+    fn test_eval_indirect()
+    {
+        use self::AddressingMode::*;
+
+        let mars = step_once(vec![
+            instr(OpCode::Mov, Modifier::I, AIndirect, 1, BIndirect, 2),
+            instr(OpCode::Dat, Modifier::F, Direct, 1, Direct, 0),
+            instr(OpCode::Dat, Modifier::F, Direct, 7, Direct, 2),
+        ]);
+
+        // A points 1 + 1 = 2, B points 2 + 2 = 4
+        assert_eq!(mars.memory()[2], mars.memory()[4]);
+    }
+
+    #[test]
+    fn test_eval_b_predecrement()
+    {
+        use self::AddressingMode::*;
+
+        let mars = step_once(vec![
+            instr(OpCode::Mov, Modifier::I, Direct, 2,
+                BIndirectPreDecrement, 1),
+            instr(OpCode::Dat, Modifier::F, Direct, 0, Direct, 3),
+            instr(OpCode::Dat, Modifier::F, Direct, 7, Direct, 7),
+        ]);
+
+        assert_eq!(2, mars.memory()[1].b());
+        assert_eq!(mars.memory()[2], mars.memory()[3]);
+    }
+
+    #[test]
+    fn test_eval_b_postincrement()
+    {
+        use self::AddressingMode::*;
+
+        let mars = step_once(vec![
+            instr(OpCode::Mov, Modifier::I, Direct, 2,
+                BIndirectPostIncrement, 1),
+            instr(OpCode::Dat, Modifier::F, Direct, 0, Direct, 2),
+            instr(OpCode::Dat, Modifier::F, Direct, 7, Direct, 7),
+        ]);
+
+        assert_eq!(3, mars.memory()[1].b());
+        assert_eq!(mars.memory()[2], mars.memory()[3]);
+    }
+
+    #[test]
+    fn test_eval_a_predecrement()
+    {
+        use self::AddressingMode::*;
+
+        let mars = step_once(vec![
+            instr(OpCode::Mov, Modifier::I, AIndirectPreDecrement, 1,
+                Direct, 3),
+            instr(OpCode::Dat, Modifier::F, Direct, 2, Direct, 0),
+            instr(OpCode::Dat, Modifier::F, Direct, 5, Direct, 5),
+        ]);
+
+        assert_eq!(1, mars.memory()[1].a());
+        assert_eq!(mars.memory()[2], mars.memory()[3]);
+    }
+
+    #[test]
+    fn test_eval_a_postincrement()
+    {
+        use self::AddressingMode::*;
+
+        let mars = step_once(vec![
+            instr(OpCode::Mov, Modifier::I, AIndirectPostIncrement, 1,
+                Direct, 3),
+            instr(OpCode::Dat, Modifier::F, Direct, 1, Direct, 0),
+            instr(OpCode::Dat, Modifier::F, Direct, 5, Direct, 5),
+        ]);
+
+        assert_eq!(2, mars.memory()[1].a());
+        assert_eq!(mars.memory()[2], mars.memory()[3]);
+    }
+
+    #[test]
+    fn test_eval_a_operand_before_b_operand()
+    {
+        use self::AddressingMode::*;
+
+        // A uses the pointer before the increment, B after it
+        let mars = step_once(vec![
+            instr(OpCode::Mov, Modifier::B, BIndirectPostIncrement, 1,
+                BIndirectPostIncrement, 1),
+            instr(OpCode::Dat, Modifier::F, Direct, 0, Direct, 1),
+            instr(OpCode::Dat, Modifier::F, Direct, 0, Direct, 9),
+        ]);
+
+        assert_eq!(3, mars.memory()[1].b());
+        assert_eq!(9, mars.memory()[3].b());
+    }
+
+    #[test]
+    fn test_eval_a_copied_before_b_decrements()
+    {
+        use self::AddressingMode::*;
+
+        let mars = step_once(vec![
+            instr(OpCode::Mov, Modifier::I, Direct, 1,
+                BIndirectPreDecrement, 1),
+            instr(OpCode::Dat, Modifier::F, Direct, 0, Direct, 5),
+        ]);
+
+        // the copy of the A target still holds the value before decrement
+        assert_eq!(4, mars.memory()[1].b());
+        assert_eq!(5, mars.memory()[5].b());
+    }
+
+    #[test]
+    fn test_eval_results_written_into_current_memory()
+    {
+        use self::AddressingMode::*;
+
+        // the increment of the target's B field is kept
+        let mars = step_once(vec![
+            instr(OpCode::Mov, Modifier::A, Immediate, 5,
+                BIndirectPostIncrement, 1),
+            instr(OpCode::Dat, Modifier::F, Direct, 0, Direct, 0),
+        ]);
+
+        assert_eq!((5, 1), (mars.memory()[1].a(), mars.memory()[1].b()));
+    }
+
+    #[test]
+    fn test_eval_arithmetic_uses_copy_of_b_target()
+    {
+        use self::AddressingMode::*;
+
+        // the increment of the target's B field is overwritten by the sum
+        let mars = step_once(vec![
+            instr(OpCode::Add, Modifier::AB, Immediate, 3,
+                BIndirectPostIncrement, 1),
+            instr(OpCode::Dat, Modifier::F, Direct, 0, Direct, 0),
+        ]);
+
+        assert_eq!(3, mars.memory()[1].b());
+    }
+
+    #[test]
+    fn test_eval_dat_operands_have_side_effects()
+    {
+        use self::AddressingMode::*;
+
+        let mars = step_once(vec![
+            instr(OpCode::Dat, Modifier::F, BIndirectPreDecrement, 1,
+                BIndirectPreDecrement, 1),
+            instr(OpCode::Dat, Modifier::F, Direct, 0, Direct, 5),
+        ]);
+
+        assert_eq!(3, mars.memory()[1].b());
+        assert!(mars.halted());
+    }
+
+    #[test]
+    fn test_load_normalizes_fields()
+    {
+        use self::AddressingMode::*;
+
+        let prog = Warrior::from(vec![
+            instr(OpCode::Jmp, Modifier::B, Direct, -1, Direct, 8001),
+        ]);
+
+        let mars: Mars<InstructionStruct> = MarsBuilder::new()
+            .build_and_load(vec![(0, None, &prog)])
+            .unwrap();
+
+        assert_eq!((7999, 1), (mars.memory()[0].a(), mars.memory()[0].b()));
+    }
+
+    #[test]
+    fn test_arithmetic_wraps_modulo_size()
+    {
+        use self::AddressingMode::*;
+
+        let mars = step_once(vec![
+            instr(OpCode::Sub, Modifier::F, Immediate, 5, Direct, 1),
+            instr(OpCode::Dat, Modifier::F, Direct, 3, Direct, 4),
+        ]);
+
+        // 3 - 5 and 4 - 1
+        assert_eq!((7998, 3), (mars.memory()[1].a(), mars.memory()[1].b()));
+
+        // products overflowing a field on a large core
+        let mut mars: Mars<InstructionStruct> = MarsBuilder::new()
+            .size(55440)
+            .build_and_load(vec![(0, None, &Warrior::from(vec![
+                instr(OpCode::Mul, Modifier::AB, Immediate, 50000, Direct, 1),
+                instr(OpCode::Dat, Modifier::F, Direct, 0, Direct, 50000),
+            ]))])
+            .unwrap();
+
+        let _ = mars.step();
+        assert_eq!(
+            (50000i64 * 50000).rem_euclid(55440) as Value,
+            mars.memory()[1].b()
+            );
+    }
+
+    #[test]
+    fn test_div_by_zero_terminates_process()
+    {
+        use self::AddressingMode::*;
+
+        let mut mars: Mars<InstructionStruct> = MarsBuilder::new()
+            .build_and_load(vec![(0, None, &Warrior::from(vec![
+                instr(OpCode::Spl, Modifier::B, Direct, 1, Direct, 0),
+                instr(OpCode::Div, Modifier::F, Direct, 1, Direct, 2),
+                instr(OpCode::Dat, Modifier::F, Direct, 0, Direct, 2),
+                instr(OpCode::Dat, Modifier::F, Direct, 9, Direct, 8),
+            ]))])
+            .unwrap();
+
+        assert_eq!(Ok(SimulationEvent::Split), mars.step());
+
+        // the B field is still divided
+        assert_eq!(Ok(SimulationEvent::Terminated), mars.step());
+        assert_eq!((9, 4), (mars.memory()[3].a(), mars.memory()[3].b()));
+        assert_eq!(1, mars.processes_of(0));
+
+        assert_eq!(
+            Ok(SimulationEvent::Halted { pid: 0, cycle: 2 }),
+            mars.step()
+            );
+        assert_eq!((9, 2), (mars.memory()[3].a(), mars.memory()[3].b()));
+    }
+
+    #[test]
+    fn test_mod_by_zero_terminates_process()
+    {
+        use self::AddressingMode::*;
+
+        let mars = step_once(vec![
+            instr(OpCode::Mod, Modifier::AB, Immediate, 0, Direct, 1),
+            instr(OpCode::Dat, Modifier::F, Direct, 0, Direct, 7),
+        ]);
+
+        assert_eq!(7, mars.memory()[1].b());
+        assert!(mars.halted());
+
+        let mars = step_once(vec![
+            instr(OpCode::Mod, Modifier::AB, Immediate, 4, Direct, 1),
+            instr(OpCode::Dat, Modifier::F, Direct, 0, Direct, 7),
+        ]);
+
+        assert_eq!(3, mars.memory()[1].b());
+        assert_eq!(1, mars.pc());
+    }
+
+    #[test]
+    fn test_decrement_wraps_below_zero()
+    {
+        use self::AddressingMode::*;
+
+        let mars = step_once(vec![
+            instr(OpCode::Djn, Modifier::B, Direct, 2, Direct, 1),
+            instr(OpCode::Dat, Modifier::F, Direct, 0, Direct, 0),
+        ]);
+
+        assert_eq!(7999, mars.memory()[1].b());
+        assert_eq!(2, mars.pc());
+    }
+
+    #[test]
+    fn test_signed_display()
+    {
+        let prog = Warrior::from(vec![
+            instr(OpCode::Dat, Modifier::F, AddressingMode::Direct, -1,
+                AddressingMode::Direct, 4000),
+        ]);
+
+        let mars: Mars<InstructionStruct> = MarsBuilder::new()
+            .build_and_load(vec![(0, None, &prog)])
+            .unwrap();
+        assert_eq!(7999, mars.display_instruction(0).a());
+
+        let mars: Mars<InstructionStruct> = MarsBuilder::new()
+            .signed_display(true)
+            .build_and_load(vec![(0, None, &prog)])
+            .unwrap();
+
+        let displayed = mars.display_instruction(0);
+        assert_eq!((-1, 4000), (displayed.a(), displayed.b()));
+        assert_eq!(7999, mars.memory()[0].a());
+    }
+
+    #[test]
+    fn test_read_limit_folds_reads()
+    {
+        use self::AddressingMode::*;
+
+        let prog = Warrior::from(vec![
+            instr(OpCode::Mov, Modifier::I, Direct, 150, Direct, 2),
+            instr(OpCode::Jmp, Modifier::B, Direct, 160, Direct, 0),
+        ]);
+
+        let mut mars: Mars<InstructionStruct> = MarsBuilder::new()
+            .read_limit(100)
+            .build_and_load(vec![(1000, None, &prog)])
+            .unwrap();
+
+        mars.memory[1050] = instr(OpCode::Dat, Modifier::F, Direct, 5,
+            Direct, 5);
+
+        // 150 is folded to 50
+        let _ = mars.step();
+        assert_eq!(mars.memory()[1050], mars.memory()[1002]);
+
+        // 160 is folded to -40
+        let _ = mars.step();
+        assert_eq!(1001 - 40, mars.pc());
+    }
+
+    #[test]
+    fn test_write_limit_folds_writes()
+    {
+        use self::AddressingMode::*;
+
+        let prog = Warrior::from(vec![
+            instr(OpCode::Mov, Modifier::I, Direct, 1, BIndirect, 1),
+            instr(OpCode::Dat, Modifier::F, Direct, 0, Direct, 70),
+        ]);
+
+        let mut mars: Mars<InstructionStruct> = MarsBuilder::new()
+            .write_limit(100)
+            .build_and_load(vec![(1000, None, &prog)])
+            .unwrap();
+
+        // 1 + 70 is folded to -29
+        let _ = mars.step();
+        assert_eq!(mars.memory()[1001], mars.memory()[1000 - 29]);
+        assert_eq!(InstructionStruct::default(), mars.memory()[1071]);
+        assert_eq!(100, mars.write_limit());
+        assert_eq!(8000, mars.read_limit());
+    }
+
+    #[test]
+    fn test_load_rejects_instructions_outside_standard()
+    {
+        use self::AddressingMode::*;
+
+        let prog = Warrior::from(vec![
+            instr(OpCode::Mul, Modifier::AB, Immediate, 2, Direct, 1),
+        ]);
+
+        let result: LoadResult<Mars<InstructionStruct>> = MarsBuilder::new()
+            .standard(Standard::Icws88)
+            .build_and_load(vec![(0, None, &prog)]);
+        assert_eq!(Err(LoadError::IllegalInstruction(0)), result.map(|_| ()));
+
+        let result: LoadResult<Mars<InstructionStruct>> = MarsBuilder::new()
+            .standard(Standard::Icws94Draft)
+            .build_and_load(vec![(0, None, &prog)]);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_load_rejects_start_outside_program()
+    {
+        let looper = looper();
+        let mut prog = looper.clone();
+        prog.set_start(500);
+
+        let result: LoadResult<Mars<InstructionStruct>> = MarsBuilder::new()
+            .build_and_load(vec![(0, None, &looper), (100, None, &prog)]);
+        assert_eq!(Err(LoadError::InvalidStart(1)), result.map(|_| ()));
+    }
+
+    #[test]
+    fn test_step_terminates_instructions_outside_standard()
+    {
+        use self::AddressingMode::*;
+
+        let prog = Warrior::from(vec![
+            instr(OpCode::Jmp, Modifier::B, Direct, 2, Direct, 0),
+        ]);
+
+        let mut mars: Mars<InstructionStruct> = MarsBuilder::new()
+            .standard(Standard::Icws88)
+            .build_and_load(vec![(0, None, &prog)])
+            .unwrap();
+
+        mars.memory[2] = instr(OpCode::Mul, Modifier::AB, Immediate, 2,
+            Direct, 1);
+        mars.memory[3] = instr(OpCode::Dat, Modifier::F, Immediate, 0,
+            Immediate, 5);
+
+        assert_eq!(Ok(SimulationEvent::Jumped), mars.step());
+        assert_eq!(
+            Ok(SimulationEvent::Halted { pid: 0, cycle: 1 }),
+            mars.step()
+            );
+        assert_eq!(5, mars.memory()[3].b());
+    }
+
+    #[test]
+    fn test_spl_order_follows_standard()
+    {
+        use self::AddressingMode::*;
+
+        let prog = Warrior::from(vec![
+            instr(OpCode::Spl, Modifier::B, Direct, 2, Direct, 0),
+        ]);
+
+        for &(standard, pc) in [(Standard::Icws86, 2), (Standard::Icws88, 1)]
+            .iter()
+        {
+            let mut mars: Mars<InstructionStruct> = MarsBuilder::new()
+                .standard(standard)
+                .build_and_load(vec![(0, None, &prog)])
+                .unwrap();
+
+            assert_eq!(Ok(SimulationEvent::Split), mars.step());
+            assert_eq!(pc, mars.pc(), "{:?}", standard);
+        }
+    }
+
+    #[test]
+    fn test_jmp_all_modes()
+    {
+        use self::AddressingMode::*;
+
+        let expected = vec![
+            (Immediate,              0),
+            (Direct,                 1),
+            (AIndirect,              3),
+            (BIndirect,              4),
+            (AIndirectPreDecrement,  2),
+            (BIndirectPreDecrement,  3),
+            (AIndirectPostIncrement, 3),
+            (BIndirectPostIncrement, 4),
+        ];
+
+        for (mode, pc) in expected {
+            let mars = step_once(vec![
+                instr(OpCode::Jmp, Modifier::B, mode, 1, Direct, 0),
+                instr(OpCode::Dat, Modifier::F, Direct, 2, Direct, 3),
+            ]);
+
+            assert_eq!(pc, mars.pc(), "{:?}", mode);
+        }
+    }
+
+    #[test]
+    fn test_jmz_indirect_target()
+    {
+        use self::AddressingMode::*;
+
+        let mars = step_once(vec![
+            instr(OpCode::Jmz, Modifier::B, BIndirect, 1, Direct, 2),
+            instr(OpCode::Dat, Modifier::F, Direct, 0, Direct, 5),
+        ]);
+
+        assert_eq!(6, mars.pc());
+    }
+
+    #[test]
+    fn test_jmn_f_jumps_if_either_field_is_non_zero()
+    {
+        use self::AddressingMode::*;
+
+        let mars = step_once(vec![
+            instr(OpCode::Jmn, Modifier::F, Direct, 3, Direct, 1),
+            instr(OpCode::Dat, Modifier::F, Direct, 0, Direct, 1),
+        ]);
+
+        assert_eq!(3, mars.pc());
+    }
+
+    #[test]
+    fn test_djn_through_postincrement()
+    {
+        use self::AddressingMode::*;
+
+        let mars = step_once(vec![
+            instr(OpCode::Djn, Modifier::B, Direct, 3,
+                BIndirectPostIncrement, 1),
+            instr(OpCode::Dat, Modifier::F, Direct, 0, Direct, 1),
+            instr(OpCode::Dat, Modifier::F, Direct, 0, Direct, 2),
+        ]);
+
+        assert_eq!(2, mars.memory()[1].b());
+        assert_eq!(1, mars.memory()[2].b());
+        assert_eq!(3, mars.pc());
+
+        // falls through once the decremented copy reaches zero
+        let mars = step_once(vec![
+            instr(OpCode::Djn, Modifier::B, Direct, 3, Direct, 1),
+            instr(OpCode::Dat, Modifier::F, Direct, 0, Direct, 1),
+        ]);
+
+        assert_eq!(0, mars.memory()[1].b());
+        assert_eq!(1, mars.pc());
+    }
+
+    #[test]
+    fn test_spl_indirect_target_queued_after_next()
+    {
+        use self::AddressingMode::*;
+
+        let mut mars = step_once(vec![
+            instr(OpCode::Spl, Modifier::B, BIndirect, 1, Direct, 0),
+            instr(OpCode::Dat, Modifier::F, Direct, 0, Direct, 4),
+        ]);
+
+        assert_eq!(1, mars.pc());
+        assert_eq!(Ok(SimulationEvent::Terminated), mars.step());
+        assert_eq!(5, mars.pc());
+    }
+
+    #[test]
+    fn test_stp_and_ldp()
+    {
+        let prog = vec![
+            InstructionStruct::new(
+                OpCode::Stp,
+                Modifier::AB,
+                7,
+                AddressingMode::Immediate,
+                512,
+                AddressingMode::Immediate,
+                ),
+            InstructionStruct::new(
+                OpCode::Ldp,
+                Modifier::AB,
+                -488,
+                AddressingMode::Immediate,
+                2,
+                AddressingMode::Direct,
+                ),
+            InstructionStruct::new(
+                OpCode::Ldp,
+                Modifier::A,
+                0,
+                AddressingMode::Immediate,
+                1,
+                AddressingMode::Direct,
+                ),
+            Default::default(),
+        ];
+
+        let mut mars: Mars<InstructionStruct> = MarsBuilder::new()
+            .pspace_size(500)
+            .build_and_load(vec![(0, Some(3), &Warrior::from(prog))])
+            .unwrap();
+
+        // locations are taken modulo the size of p-space
+        assert_eq!(Ok(SimulationEvent::Stepped), mars.step());
+        assert_eq!(7, mars.pspace()[&PSpaceKey::Pin(3)][12]);
+
+        assert_eq!(Ok(SimulationEvent::Stepped), mars.step());
+        assert_eq!(7, mars.memory()[3].b());
+
+        // location 0 starts out as -1 before any result is recorded, which
+        // is loaded modulo the size of memory
+        assert_eq!(Ok(SimulationEvent::Stepped), mars.step());
+        assert_eq!(7999, mars.memory()[3].a());
+    }
+
+    #[test]
+    fn test_pspace_shared_by_pin_and_kept_across_rounds()
+    {
+        let useless_program = Warrior::from(vec![Default::default(); 1]);
+
+        let mut mars: Mars<InstructionStruct> = MarsBuilder::new()
+            .build_and_load(vec![
+                (0, Some(9), &useless_program),
+                (100, Some(9), &useless_program),
+                (200, None, &useless_program),
+            ])
+            .unwrap();
+
+        assert_eq!(2, mars.pspace().len());
+        assert_eq!(Some(9), mars.pin(0));
+        assert_eq!(Some(9), mars.pin(1));
+        assert_eq!(None, mars.pin(2));
+        assert_eq!(Some(PSpaceKey::Private(2)), mars.pspace_key(2));
+
+        mars.set_last_result(PSpaceKey::Pin(9), 1);
+        mars.reset();
+        assert_eq!(None, mars.pin(0));
+        assert_eq!(1, mars.pspace()[&PSpaceKey::Pin(9)][0]);
+
+        mars.reset_hard();
+        assert!(mars.pspace().is_empty());
+    }
+
+    #[test]
+    fn test_private_pspace_not_shared_with_pin()
+    {
+        let useless_program = Warrior::from(vec![Default::default(); 1]);
+
+        // the first warrior has Pid 0 but no PIN
+        let mut mars: Mars<InstructionStruct> = MarsBuilder::new()
+            .build_and_load(vec![
+                (0, None, &useless_program),
+                (100, Some(0), &useless_program),
+            ])
+            .unwrap();
+
+        assert_eq!(2, mars.pspace().len());
+        assert_eq!(Some(PSpaceKey::Private(0)), mars.pspace_key(0));
+        assert_eq!(Some(PSpaceKey::Pin(0)), mars.pspace_key(1));
+
+        mars.set_last_result(PSpaceKey::Private(0), 1);
+        assert_eq!(1, mars.pspace()[&PSpaceKey::Private(0)][0]);
+        assert_eq!(-1, mars.pspace()[&PSpaceKey::Pin(0)][0]);
+    }
+
+    #[test]
+    fn test_spl_cant_create_more_than_max_processes()
+    {
+        // splitter program, infinitely creates imps
+        let prog = vec![
+            InstructionStruct::new(
+                OpCode::Spl,
+                Modifier::I,
+                2,
+                AddressingMode::Direct,
+                1,
+                AddressingMode::Direct,
+                ),
+            InstructionStruct::new(
+                OpCode::Jmp,
+                Modifier::I,
+                -1,
+                AddressingMode::Direct,
+                1,
+                AddressingMode::Direct,
+                ),
+            InstructionStruct::new(
+                OpCode::Mov,
+                Modifier::I,
+                0,
+                AddressingMode::Direct,
+                1,
+                AddressingMode::Direct,
+            ),
+        ];
+
+        let mut mars: Mars<InstructionStruct> = MarsBuilder::new()
+            .max_processes(10)
+            .build_and_load(vec![(0, None, &Warrior::from(prog.clone()))])
+            .unwrap();
+
+        assert_eq!(Ok(SimulationEvent::Split), mars.step());
+
+        // run the simulation until it halts because cycles have been exauste
+        while !mars.halted() {
+            let _ = mars.step();
+        }
+        
+        assert_eq!(10, mars.processes_of(0));
+    }
+
+    #[test]
+    fn test_max_processes_is_per_warrior()
+    {
+        use self::AddressingMode::*;
+
+        let splitter = Warrior::from(vec![
+            instr(OpCode::Spl, Modifier::B, Direct, 0, Direct, 0),
+            instr(OpCode::Jmp, Modifier::B, Direct, -1, Direct, 0),
+        ]);
+
+        let mut mars: Mars<InstructionStruct> = MarsBuilder::limited_process()
+            .max_cycles(1000)
+            .build_and_load(vec![(0, None, &splitter), (4000, None, &splitter)])
+            .unwrap();
+
+        while !mars.halted() {
+            let _ = mars.step();
+        }
+
+        assert_eq!((8, 8), (mars.processes_of(0), mars.processes_of(1)));
     }
 }
 
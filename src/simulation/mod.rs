@@ -5,9 +5,11 @@ pub use self::mars::{
     Mars,
     LoadResult,
     LoadError,
+    PSpaceKey,
     SimulationResult,
     SimulationEvent,
-    SimulationError
+    SimulationError,
+    MatchOutcome
 };
 
 mod builder;
@@ -16,4 +18,17 @@ pub use self::builder::{
     BuilderError
 };
 
+mod placement;
+pub use self::placement::{
+    Placement,
+    Rng
+};
+
+mod battle;
+pub use self::battle::{
+    Match,
+    MatchResults,
+    RoundResult
+};
+
 
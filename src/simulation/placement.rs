@@ -0,0 +1,288 @@
+//! Choosing where warriors are loaded in the core
+
+use std::iter::once;
+
+use redcode::types::*;
+
+use simulation::{LoadResult, LoadError};
+
+/// Largest value produced by `Rng`, plus one
+const RNG_MODULUS: u32 = 2_147_483_647;
+
+/// Seedable random number generator. Produces the same sequence as the one
+/// pMARS uses to place warriors
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Rng
+{
+    /// Last value produced, the seed of the next one
+    state: u32,
+}
+
+impl Rng
+{
+    /// Create a generator from a seed. A seed of `0` is replaced by `1`,
+    /// which the generator could not move away from
+    ///
+    /// # Arguments
+    /// * `seed`: seed of the generator
+    pub fn new(seed: u32) -> Self
+    {
+        match seed % RNG_MODULUS {
+            0     => Rng { state: 1 },
+            state => Rng { state },
+        }
+    }
+
+    /// Current seed of the generator. A generator created from it produces
+    /// the same values from this point on
+    pub fn seed(&self) -> u32
+    {
+        self.state
+    }
+
+    /// Produce the next value of the sequence
+    ///
+    /// # Return
+    /// value between `1` and `2^31 - 2`
+    pub fn next_value(&mut self) -> u32
+    {
+        let state = self.state as i64;
+        let mut next = 16807 * (state % 127_773) - 2836 * (state / 127_773);
+        if next < 0 {
+            next += RNG_MODULUS as i64;
+        }
+
+        self.state = next as u32;
+        self.state
+    }
+
+    /// Produce a value below a bound
+    ///
+    /// # Arguments
+    /// * `bound`: exclusive upper bound, cannot be `0`
+    pub fn below(&mut self, bound: usize) -> usize
+    {
+        self.next_value() as usize % bound
+    }
+}
+
+/// How a `Mars` chooses where warriors are loaded. The first warrior is
+/// always loaded at `0`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Placement
+{
+    /// Other warriors are loaded at random positions at least
+    /// `min_distance` away from each other, like pMARS does
+    Random(Rng),
+
+    /// The second warrior is loaded at the address and the others follow it
+    /// `min_distance` apart. Reproduces a specific round. Positions that
+    /// bring two warriors closer than `min_distance` around the core are
+    /// rejected
+    Fixed(Address),
+}
+
+impl Placement
+{
+    /// Random placement from a seed
+    ///
+    /// # Arguments
+    /// * `seed`: seed of the random number generator
+    pub fn seeded(seed: u32) -> Self
+    {
+        Placement::Random(Rng::new(seed))
+    }
+
+    /// Choose the load position of each warrior. Random placements advance
+    /// their generator, so successive calls give the positions of successive
+    /// rounds
+    ///
+    /// # Arguments
+    /// * `count`: number of warriors
+    /// * `size`: size of the core
+    /// * `min_distance`: minimum distance between two warriors
+    ///
+    /// # Return
+    /// position of each warrior, in load order, on success. Otherwise the
+    /// error loading warriors at them would give
+    pub fn positions(&mut self, count: usize, size: usize, min_distance: usize)
+        -> LoadResult<Vec<Address>>
+    {
+        match count {
+            0 => return Err(LoadError::EmptyLoad),
+            1 => return Ok(vec![0]),
+            _ => {}
+        }
+
+        // the last warrior would wrap around too close to the first
+        if count * min_distance > size {
+            return Err(LoadError::InvalidDistance(0, count - 1));
+        }
+
+        match *self {
+            Placement::Random(ref mut rng) =>
+                Ok(random_positions(rng, count, size, min_distance)),
+            Placement::Fixed(second) => {
+                let positions: Vec<Address> = once(0)
+                    .chain((1..count).map(|i| {
+                        let pos = second as usize + (i - 1) * min_distance;
+                        (pos % size) as Address
+                    }))
+                    .collect();
+
+                check_distances(&positions, size, min_distance)?;
+                Ok(positions)
+            }
+        }
+    }
+}
+
+/// Check that warriors are at least `min_distance` apart around the core
+///
+/// # Arguments
+/// * `positions`: position of each warrior, in load order
+/// * `size`: size of the core
+/// * `min_distance`: minimum distance between two warriors
+///
+/// # Return
+/// `InvalidDistance` with the first pair of warriors that are too close
+fn check_distances(positions: &[Address], size: usize, min_distance: usize)
+    -> LoadResult<()>
+{
+    for (i, &first) in positions.iter().enumerate() {
+        for (j, &second) in positions.iter().enumerate().skip(i + 1) {
+            let d = (first as usize % size + size - second as usize % size)
+                % size;
+            if d.min(size - d) < min_distance {
+                return Err(LoadError::InvalidDistance(i, j));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Random positions for warriors, the first one at `0`. The spare room left
+/// once every warrior has `min_distance` for itself is split randomly between
+/// the gaps, then the warriors after the first are shuffled
+///
+/// # Arguments
+/// * `rng`: random number generator
+/// * `count`: number of warriors, at least `2`
+/// * `size`: size of the core
+/// * `min_distance`: minimum distance between two warriors
+fn random_positions(
+    rng:          &mut Rng,
+    count:        usize,
+    size:         usize,
+    min_distance: usize,
+    ) -> Vec<Address>
+{
+    let spare = size - count * min_distance;
+
+    let mut offsets: Vec<usize> = (1..count)
+        .map(|_| rng.below(spare + 1))
+        .collect();
+    offsets.sort();
+
+    let mut positions: Vec<Address> = offsets.iter()
+        .enumerate()
+        .map(|(i, offset)| ((i + 1) * min_distance + offset) as Address)
+        .collect();
+
+    for i in (1..positions.len()).rev() {
+        let j = rng.below(i + 1);
+        positions.swap(i, j);
+    }
+
+    positions.insert(0, 0);
+    positions
+}
+
+#[cfg(test)]
+mod test
+{
+    use super::*;
+
+    #[test]
+    fn test_rng_is_reproducible()
+    {
+        let mut rng = Rng::new(42);
+        assert_eq!(705_894, rng.next_value());
+
+        let mut copy = Rng::new(rng.seed());
+        assert_eq!(rng.next_value(), copy.next_value());
+
+        assert_eq!(Rng::new(1), Rng::new(0));
+    }
+
+    #[test]
+    fn test_random_positions_respect_min_distance()
+    {
+        let (size, min_distance) = (1000, 100);
+        let mut placement = Placement::seeded(7);
+
+        for _ in 0..100 {
+            let positions = placement.positions(4, size, min_distance).unwrap();
+            assert_eq!(0, positions[0]);
+
+            for (i, &first) in positions.iter().enumerate() {
+                for &second in positions.iter().skip(i + 1) {
+                    let d = (first as i64 - second as i64).unsigned_abs() as usize;
+                    assert!(d.min(size - d) >= min_distance);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_random_positions_cover_window()
+    {
+        // the second warrior can be anywhere from 100 to 200
+        let mut placement = Placement::seeded(1);
+        let mut seen = [false; 101];
+
+        for _ in 0..10_000 {
+            let positions = placement.positions(2, 300, 100).unwrap();
+            seen[positions[1] as usize - 100] = true;
+        }
+
+        assert!(seen.iter().all(|&s| s));
+    }
+
+    #[test]
+    fn test_fixed_positions()
+    {
+        let mut placement = Placement::Fixed(150);
+
+        assert_eq!(Ok(vec![0, 150, 250]), placement.positions(3, 400, 100));
+        assert_eq!(Ok(vec![0]), placement.positions(1, 400, 100));
+        assert_eq!(
+            Err(LoadError::InvalidDistance(0, 4)),
+            placement.positions(5, 400, 100)
+            );
+        assert_eq!(Err(LoadError::EmptyLoad), placement.positions(0, 400, 100));
+    }
+
+    #[test]
+    fn test_fixed_positions_check_wrapped_distance()
+    {
+        // 7950 is only 50 away from 0 once the core wraps around
+        assert_eq!(
+            Err(LoadError::InvalidDistance(0, 1)),
+            Placement::Fixed(7950).positions(2, 8000, 100)
+            );
+        assert_eq!(
+            Err(LoadError::InvalidDistance(0, 1)),
+            Placement::Fixed(50).positions(2, 8000, 100)
+            );
+        assert_eq!(
+            Err(LoadError::InvalidDistance(0, 2)),
+            Placement::Fixed(250).positions(3, 400, 100)
+            );
+        assert_eq!(
+            Ok(vec![0, 7900]),
+            Placement::Fixed(7900).positions(2, 8000, 100)
+            );
+    }
+}